@@ -0,0 +1,124 @@
+use bytes::Bytes;
+use criterion::{BatchSize, Criterion, black_box, criterion_group, criterion_main};
+use rapid_tlv::{RapidTlvMessage, RapidTlvWireConfig, RapidTlvWireFormat};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::sync::atomic::{AtomicUsize, Ordering};
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_TTL: u16 = 0x03;
+pub const FIELD_ID: u16 = 0x08;
+
+// Counts heap bytes so the benchmark can report per-message memory footprint
+struct CountingAlloc;
+
+static ALLOCATED: AtomicUsize = AtomicUsize::new(0);
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATED.fetch_add(layout.size(), Ordering::Relaxed);
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn small_message() -> RapidTlvMessage {
+    RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"user:1234"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"{\"name\":\"test\"}"))
+}
+
+fn medium_message() -> RapidTlvMessage {
+    small_message()
+        .with_field(FIELD_TTL, Bytes::from_static(&[0, 0, 0, 60]))
+        .with_field(FIELD_ID, Bytes::from_static(b"0123456789abcdef"))
+}
+
+fn heap_bytes<T>(f: impl FnOnce() -> T) -> usize {
+    let before = ALLOCATED.load(Ordering::Relaxed);
+    let value = f();
+    let allocated = ALLOCATED.load(Ordering::Relaxed) - before;
+    drop(value);
+    allocated
+}
+
+fn report_footprint() {
+    let encoded = Bytes::copy_from_slice(medium_message().encode().unwrap());
+    let parsed = RapidTlvMessage::parse(encoded.clone()).unwrap();
+
+    println!(
+        "footprint: size_of::<Message>() = {} bytes, parse allocates {} bytes, clone allocates {} bytes",
+        std::mem::size_of::<RapidTlvMessage>(),
+        heap_bytes(|| RapidTlvMessage::parse(encoded.clone()).unwrap()),
+        heap_bytes(|| parsed.clone()),
+    );
+}
+
+fn report_wire_size() {
+    let compact = RapidTlvWireConfig::legacy().with_format(RapidTlvWireFormat::compact());
+    for (name, msg) in [("small", small_message()), ("medium", medium_message())] {
+        println!(
+            "bytes on wire/{name}: default = {} bytes, compact = {} bytes",
+            msg.to_bytes().unwrap().len(),
+            msg.to_bytes_with(&compact).unwrap().len(),
+        );
+    }
+}
+
+fn bench_message(c: &mut Criterion) {
+    report_footprint();
+    report_wire_size();
+
+    for (name, msg) in [("small", small_message()), ("medium", medium_message())] {
+        let mut msg = msg;
+        let encoded = Bytes::copy_from_slice(msg.encode().unwrap());
+
+        c.bench_function(&format!("parse/{name}"), |b| {
+            b.iter(|| RapidTlvMessage::parse(black_box(encoded.clone())).unwrap())
+        });
+
+        c.bench_function(&format!("encode/{name}"), |b| {
+            b.iter_batched(
+                || {
+                    // A freshly modified message has no cached encoding
+                    let mut m = msg.clone();
+                    m.add_field(FIELD_KEY, Bytes::from_static(b"user:1234"));
+                    m
+                },
+                |mut m| m.encode().unwrap().len(),
+                BatchSize::SmallInput,
+            )
+        });
+
+        let parsed = RapidTlvMessage::parse(encoded.clone()).unwrap();
+        c.bench_function(&format!("clone/{name}"), |b| {
+            b.iter(|| black_box(&parsed).clone())
+        });
+
+        c.bench_function(&format!("get_field/{name}"), |b| {
+            b.iter(|| black_box(&parsed).get_field(&FIELD_KEY).is_some())
+        });
+
+        let compact = RapidTlvWireConfig::legacy().with_format(RapidTlvWireFormat::compact());
+        let compact_encoded = msg.to_bytes_with(&compact).unwrap();
+        c.bench_function(&format!("parse_compact/{name}"), |b| {
+            b.iter(|| {
+                RapidTlvMessage::parse_with(black_box(compact_encoded.clone()), &compact).unwrap()
+            })
+        });
+        c.bench_function(&format!("encode_compact/{name}"), |b| {
+            b.iter(|| black_box(&msg).to_bytes_with(&compact).unwrap().len())
+        });
+    }
+}
+
+criterion_group!(benches, bench_message);
+criterion_main!(benches);
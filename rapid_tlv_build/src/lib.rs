@@ -0,0 +1,345 @@
+//! Generates Rust code from `rapid_tlv` schema files, for use in `build.rs`.
+//!
+//! ```ignore
+//! // build.rs
+//! fn main() {
+//!     rapid_tlv_build::compile("schema/kv.tlv").unwrap();
+//! }
+//!
+//! // src/lib.rs
+//! include!(concat!(env!("OUT_DIR"), "/kv.rs"));
+//! ```
+//!
+//! For every field the generated code has a `FIELD_<NAME>` constant, for every
+//! event an `EVT_<NAME>` constant and a struct implementing `TlvMessage`, and
+//! a `validate` function that checks a message against its event.
+
+use rapid_tlv::{
+    RapidTlvError, RapidTlvErrorCode, RapidTlvEventSchema, RapidTlvPresence, RapidTlvSchema,
+    RapidTlvValueType,
+};
+use std::fmt::Write;
+use std::path::Path;
+
+/// Generates code for the schema at `path` into `$OUT_DIR/<file stem>.rs`.
+pub fn compile(path: impl AsRef<Path>) -> Result<(), RapidTlvError> {
+    let path = path.as_ref();
+    let out_dir = std::env::var_os("OUT_DIR").ok_or_else(|| {
+        RapidTlvError::new(
+            RapidTlvErrorCode::WriteFailed,
+            "OUT_DIR is not set; compile() must be called from build.rs".into(),
+        )
+    })?;
+    let stem = path.file_stem().ok_or_else(|| {
+        RapidTlvError::new(
+            RapidTlvErrorCode::ReadFailed,
+            format!("{} is not a file", path.display()),
+        )
+    })?;
+
+    println!("cargo:rerun-if-changed={}", path.display());
+    compile_to(path, Path::new(&out_dir).join(stem).with_extension("rs"))
+}
+
+/// Generates code for the schema at `path` into `out`.
+pub fn compile_to(path: impl AsRef<Path>, out: impl AsRef<Path>) -> Result<(), RapidTlvError> {
+    let source = std::fs::read_to_string(path)?;
+    let code = generate(&RapidTlvSchema::parse(&source)?)?;
+    std::fs::write(out, code).map_err(|e| {
+        RapidTlvError::new(
+            RapidTlvErrorCode::WriteFailed,
+            format!("Failed to write generated code: {e}"),
+        )
+    })
+}
+
+/// Generates the Rust source for `schema`, after checking it.
+pub fn generate(schema: &RapidTlvSchema) -> Result<String, RapidTlvError> {
+    schema.check()?;
+
+    let mut out = String::from("// Generated by rapid_tlv_build. Do not edit.\n\n");
+
+    for event in schema.events() {
+        writeln!(
+            out,
+            "pub const EVT_{}: ::rapid_tlv::RapidTlvEventType = {:#04x};",
+            event.name.to_uppercase(),
+            event.event_type
+        )
+        .unwrap();
+    }
+    out.push('\n');
+    for field in schema.fields() {
+        writeln!(
+            out,
+            "pub const FIELD_{}: ::rapid_tlv::RapidTlvFieldType = {:#04x};",
+            field.name.to_uppercase(),
+            field.field_type
+        )
+        .unwrap();
+    }
+
+    for event in schema.events() {
+        out.push('\n');
+        generate_event(&mut out, schema, event);
+    }
+
+    out.push_str(
+        "\n/// Checks that `msg` is a known event with all required fields and valid values.\n\
+         pub fn validate(\n    \
+             msg: &::rapid_tlv::RapidTlvMessage,\n\
+         ) -> ::std::result::Result<(), ::rapid_tlv::RapidTlvError> {\n    \
+             match msg.event_type {\n",
+    );
+    for event in schema.events() {
+        writeln!(
+            out,
+            "        EVT_{} => <{} as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),",
+            event.name.to_uppercase(),
+            type_name(&event.name)
+        )
+        .unwrap();
+    }
+    out.push_str(
+        "        other => ::std::result::Result::Err(::rapid_tlv::__private::unknown_event_type(other)),\n    \
+             }\n\
+         }\n",
+    );
+    Ok(out)
+}
+
+fn generate_event(out: &mut String, schema: &RapidTlvSchema, event: &RapidTlvEventSchema) {
+    let name = type_name(&event.name);
+    let evt = format!("EVT_{}", event.name.to_uppercase());
+
+    // Resolved fields: (struct field, FIELD_ constant, element type, presence)
+    let fields: Vec<_> = event
+        .fields
+        .iter()
+        .map(|used| {
+            let field = schema
+                .field(&used.name)
+                .expect("schema references are checked by generate");
+            let mut ty = rust_type(&field.value_type);
+            // A struct can only contain itself behind a pointer
+            if let RapidTlvValueType::Event(target) = &field.value_type
+                && used.presence != RapidTlvPresence::Repeated
+                && contains(schema, target, &event.name, &mut Vec::new())
+            {
+                ty = format!("::std::boxed::Box<{ty}>");
+            }
+            (
+                field_ident(&field.name),
+                format!("FIELD_{}", field.name.to_uppercase()),
+                ty,
+                used.presence,
+            )
+        })
+        .collect();
+
+    writeln!(
+        out,
+        "#[derive(Debug, Clone, PartialEq)]\npub struct {name} {{"
+    )
+    .unwrap();
+    for (ident, _, ty, presence) in &fields {
+        let ty = match presence {
+            RapidTlvPresence::Required => ty.clone(),
+            RapidTlvPresence::Optional => format!("::std::option::Option<{ty}>"),
+            RapidTlvPresence::Repeated => format!("::std::vec::Vec<{ty}>"),
+        };
+        writeln!(out, "    pub {ident}: {ty},").unwrap();
+    }
+    out.push_str("}\n\n");
+
+    writeln!(
+        out,
+        "impl ::rapid_tlv::TlvMessage for {name} {{\n    \
+             const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = {evt};\n\n    \
+             fn to_message(\n        \
+                 &self,\n    \
+             ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {{\n        \
+                 let mut msg = ::rapid_tlv::RapidTlvMessage::new({evt});"
+    )
+    .unwrap();
+    for (ident, constant, _, presence) in &fields {
+        match presence {
+            RapidTlvPresence::Required => writeln!(
+                out,
+                "        msg.add_field({constant}, ::rapid_tlv::TlvValue::encode_value(&self.{ident})?);"
+            ),
+            RapidTlvPresence::Optional => writeln!(
+                out,
+                "        if let ::std::option::Option::Some(value) = &self.{ident} {{\n            \
+                     msg.add_field({constant}, ::rapid_tlv::TlvValue::encode_value(value)?);\n        \
+                 }}"
+            ),
+            RapidTlvPresence::Repeated => writeln!(
+                out,
+                "        for value in &self.{ident} {{\n            \
+                     msg.add_repeated_field({constant}, ::rapid_tlv::TlvValue::encode_value(value)?);\n        \
+                 }}"
+            ),
+        }
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "        ::std::result::Result::Ok(msg)\n    \
+         }}\n\n    \
+         fn from_message(\n        \
+             msg: &::rapid_tlv::RapidTlvMessage,\n    \
+         ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {{\n        \
+             if msg.event_type != {evt} {{\n            \
+                 return ::std::result::Result::Err(\n                \
+                     ::rapid_tlv::__private::unexpected_event_type({evt}, msg.event_type),\n            \
+                 );\n        \
+             }}\n        \
+             ::std::result::Result::Ok(Self {{"
+    )
+    .unwrap();
+    for (ident, constant, ty, presence) in &fields {
+        match presence {
+            RapidTlvPresence::Required => writeln!(
+                out,
+                "            {ident}: match msg.get_field(&{constant}) {{\n                \
+                     ::std::option::Option::Some(field) => <{ty} as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,\n                \
+                     ::std::option::Option::None => {{\n                    \
+                         return ::std::result::Result::Err(::rapid_tlv::__private::missing_field({constant}));\n                \
+                     }}\n            \
+                 }},"
+            ),
+            RapidTlvPresence::Optional => writeln!(
+                out,
+                "            {ident}: msg\n                \
+                     .get_field(&{constant})\n                \
+                     .map(|field| <{ty} as ::rapid_tlv::TlvValue>::decode_field(msg, field))\n                \
+                     .transpose()?,"
+            ),
+            RapidTlvPresence::Repeated => writeln!(
+                out,
+                "            {ident}: msg\n                \
+                     .get_fields(&{constant})\n                \
+                     .map(|field| <{ty} as ::rapid_tlv::TlvValue>::decode_field(msg, field))\n                \
+                     .collect::<::std::result::Result<_, _>>()?,"
+            ),
+        }
+        .unwrap();
+    }
+    writeln!(
+        out,
+        "        }})\n    \
+         }}\n\
+         }}\n\n\
+         impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for {name} {{\n    \
+             type Error = ::rapid_tlv::RapidTlvError;\n\n    \
+             fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {{\n        \
+                 <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)\n    \
+             }}\n\
+         }}\n\n\
+         impl ::rapid_tlv::TlvValue for {name} {{\n    \
+             fn encode_value(\n        \
+                 &self,\n    \
+             ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {{\n        \
+                 ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()\n    \
+             }}\n\n    \
+             fn decode_value(\n        \
+                 field: &::rapid_tlv::RapidTlvField,\n    \
+             ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {{\n        \
+                 let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;\n        \
+                 <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)\n    \
+             }}\n\
+             \n    \
+             fn decode_field(\n        \
+                 msg: &::rapid_tlv::RapidTlvMessage,\n        \
+                 field: &::rapid_tlv::RapidTlvField,\n    \
+             ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {{\n        \
+                 let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;\n        \
+                 <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)\n    \
+             }}\n\
+         }}"
+    )
+    .unwrap();
+}
+
+fn rust_type(value_type: &RapidTlvValueType) -> String {
+    match value_type {
+        RapidTlvValueType::U8 => "u8".into(),
+        RapidTlvValueType::U16 => "u16".into(),
+        RapidTlvValueType::U32 => "u32".into(),
+        RapidTlvValueType::U64 => "u64".into(),
+        RapidTlvValueType::I64 => "i64".into(),
+        RapidTlvValueType::F64 => "f64".into(),
+        RapidTlvValueType::Bool => "bool".into(),
+        RapidTlvValueType::String => "::std::string::String".into(),
+        RapidTlvValueType::Bytes => "::std::vec::Vec<u8>".into(),
+        RapidTlvValueType::Message => "::rapid_tlv::RapidTlvMessage".into(),
+        RapidTlvValueType::Event(event) => type_name(event),
+    }
+}
+
+// Whether the struct for event `name` holds the one for `target` inline
+fn contains<'a>(
+    schema: &'a RapidTlvSchema,
+    name: &'a str,
+    target: &str,
+    seen: &mut Vec<&'a str>,
+) -> bool {
+    if name == target {
+        return true;
+    }
+    if seen.contains(&name) {
+        return false;
+    }
+    seen.push(name);
+    let Some(event) = schema.event(name) else {
+        return false;
+    };
+    event.fields.iter().any(|used| {
+        used.presence != RapidTlvPresence::Repeated
+            && matches!(
+                schema.field(&used.name).map(|f| &f.value_type),
+                Some(RapidTlvValueType::Event(next)) if contains(schema, next, target, seen)
+            )
+    })
+}
+
+// get_reply -> GetReply, self -> Self_
+fn type_name(name: &str) -> String {
+    let name: String = name
+        .split('_')
+        .map(|part| {
+            let mut chars = part.chars();
+            match chars.next() {
+                Some(first) => first.to_uppercase().chain(chars).collect(),
+                None => String::new(),
+            }
+        })
+        .collect();
+    if name == "Self" {
+        format!("{name}_")
+    } else {
+        name
+    }
+}
+
+const KEYWORDS: &[&str] = &[
+    "abstract", "as", "async", "await", "become", "box", "break", "const", "continue", "do", "dyn",
+    "else", "enum", "extern", "false", "final", "fn", "for", "gen", "if", "impl", "in", "let",
+    "loop", "macro", "match", "mod", "move", "mut", "override", "priv", "pub", "ref", "return",
+    "static", "struct", "trait", "true", "try", "type", "typeof", "unsafe", "unsized", "use",
+    "virtual", "where", "while", "yield",
+];
+
+// Keywords that cannot be raw identifiers either
+const PATH_KEYWORDS: &[&str] = &["crate", "self", "Self", "super"];
+
+fn field_ident(name: &str) -> String {
+    if PATH_KEYWORDS.contains(&name) {
+        format!("{name}_")
+    } else if KEYWORDS.contains(&name) {
+        format!("r#{name}")
+    } else {
+        name.to_string()
+    }
+}
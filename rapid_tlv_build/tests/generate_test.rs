@@ -0,0 +1,112 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvMessage, RapidTlvSchema, TlvMessage};
+
+mod kv {
+    include!("generated/kv.rs");
+}
+
+mod tree {
+    include!("generated/tree.rs");
+}
+
+mod keywords {
+    include!("generated/keywords.rs");
+}
+
+use kv::{Batch, Get, Set};
+
+#[test]
+fn test_generated_code_is_current() {
+    for (source, generated) in [
+        (include_str!("kv.tlv"), include_str!("generated/kv.rs")),
+        (include_str!("tree.tlv"), include_str!("generated/tree.rs")),
+        (
+            include_str!("keywords.tlv"),
+            include_str!("generated/keywords.rs"),
+        ),
+    ] {
+        let schema = RapidTlvSchema::parse(source).unwrap();
+        assert_eq!(rapid_tlv_build::generate(&schema).unwrap(), generated);
+    }
+}
+
+#[test]
+fn test_generated_roundtrip() {
+    assert_eq!(kv::EVT_SET, 0x10);
+    assert_eq!(kv::FIELD_GROUP, 0x05);
+
+    let set = Set {
+        key: "test_key".into(),
+        value: b"test_value".to_vec(),
+        ttl: Some(60),
+        persist: None,
+        group: vec!["a".into(), "b".into()],
+    };
+    let batch = Batch {
+        id: 7,
+        set: vec![set.clone()],
+    };
+
+    let mut msg = batch.to_message().unwrap();
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(Batch::try_from(parsed).unwrap(), batch);
+    assert_eq!(Set::from_message(&set.to_message().unwrap()).unwrap(), set);
+}
+
+#[test]
+fn test_generated_validate() {
+    let get = Get { key: "k".into() }.to_message().unwrap();
+    assert!(kv::validate(&get).is_ok());
+
+    let err = kv::validate(&RapidTlvMessage::new(kv::EVT_GET)).unwrap_err();
+    assert_eq!(err.field_type(), Some(kv::FIELD_KEY));
+
+    let err = kv::validate(&RapidTlvMessage::new(0x42)).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::InvalidEventType);
+}
+
+#[test]
+fn test_generate_checks_schema() {
+    let schema = RapidTlvSchema::new()
+        .with_event(rapid_tlv::RapidTlvEventSchema::new("get", 0x11).required("key"));
+    let err = rapid_tlv_build::generate(&schema).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert!(err.message().contains("unknown field `key`"), "{err}");
+}
+
+#[test]
+fn test_generated_recursive_roundtrip() {
+    let leaf = |name: &str| tree::Node {
+        name: name.into(),
+        next: None,
+        children: Vec::new(),
+        parent: None,
+    };
+    let root = tree::Node {
+        name: "root".into(),
+        next: Some(Box::new(leaf("next"))),
+        children: vec![leaf("a"), leaf("b")],
+        parent: Some(Box::new(tree::Link {
+            node: Box::new(leaf("up")),
+        })),
+    };
+
+    let msg = root.to_message().unwrap();
+    let parsed = RapidTlvMessage::parse(msg.to_bytes().unwrap()).unwrap();
+    assert_eq!(tree::Node::from_message(&parsed).unwrap(), root);
+    assert!(tree::validate(&parsed).is_ok());
+}
+
+#[test]
+fn test_generated_keyword_names() {
+    let value = keywords::Self_ {
+        self_: "me".into(),
+        super_: Some(1),
+        crate_: None,
+        r#type: vec![2, 3],
+    };
+    let msg = value.to_message().unwrap();
+    assert_eq!(msg.get_field(&keywords::FIELD_SELF).unwrap().value(), b"me");
+    assert_eq!(keywords::Self_::from_message(&msg).unwrap(), value);
+    assert!(keywords::validate(&msg).is_ok());
+}
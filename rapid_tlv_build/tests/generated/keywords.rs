@@ -0,0 +1,108 @@
+// Generated by rapid_tlv_build. Do not edit.
+
+pub const EVT_SELF: ::rapid_tlv::RapidTlvEventType = 0x10;
+
+pub const FIELD_SELF: ::rapid_tlv::RapidTlvFieldType = 0x01;
+pub const FIELD_SUPER: ::rapid_tlv::RapidTlvFieldType = 0x02;
+pub const FIELD_CRATE: ::rapid_tlv::RapidTlvFieldType = 0x03;
+pub const FIELD_TYPE: ::rapid_tlv::RapidTlvFieldType = 0x04;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Self_ {
+    pub self_: ::std::string::String,
+    pub super_: ::std::option::Option<u32>,
+    pub crate_: ::std::option::Option<bool>,
+    pub r#type: ::std::vec::Vec<u8>,
+}
+
+impl ::rapid_tlv::TlvMessage for Self_ {
+    const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = EVT_SELF;
+
+    fn to_message(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+        let mut msg = ::rapid_tlv::RapidTlvMessage::new(EVT_SELF);
+        msg.add_field(FIELD_SELF, ::rapid_tlv::TlvValue::encode_value(&self.self_)?);
+        if let ::std::option::Option::Some(value) = &self.super_ {
+            msg.add_field(FIELD_SUPER, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        if let ::std::option::Option::Some(value) = &self.crate_ {
+            msg.add_field(FIELD_CRATE, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        for value in &self.r#type {
+            msg.add_repeated_field(FIELD_TYPE, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        ::std::result::Result::Ok(msg)
+    }
+
+    fn from_message(
+        msg: &::rapid_tlv::RapidTlvMessage,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        if msg.event_type != EVT_SELF {
+            return ::std::result::Result::Err(
+                ::rapid_tlv::__private::unexpected_event_type(EVT_SELF, msg.event_type),
+            );
+        }
+        ::std::result::Result::Ok(Self {
+            self_: match msg.get_field(&FIELD_SELF) {
+                ::std::option::Option::Some(field) => <::std::string::String as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_SELF));
+                }
+            },
+            super_: msg
+                .get_field(&FIELD_SUPER)
+                .map(|field| <u32 as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .transpose()?,
+            crate_: msg
+                .get_field(&FIELD_CRATE)
+                .map(|field| <bool as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .transpose()?,
+            r#type: msg
+                .get_fields(&FIELD_TYPE)
+                .map(|field| <u8 as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .collect::<::std::result::Result<_, _>>()?,
+        })
+    }
+}
+
+impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for Self_ {
+    type Error = ::rapid_tlv::RapidTlvError;
+
+    fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+}
+
+impl ::rapid_tlv::TlvValue for Self_ {
+    fn encode_value(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+        ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+    }
+
+    fn decode_value(
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+
+    fn decode_field(
+        msg: &::rapid_tlv::RapidTlvMessage,
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+    }
+}
+
+/// Checks that `msg` is a known event with all required fields and valid values.
+pub fn validate(
+    msg: &::rapid_tlv::RapidTlvMessage,
+) -> ::std::result::Result<(), ::rapid_tlv::RapidTlvError> {
+    match msg.event_type {
+        EVT_SELF => <Self_ as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),
+        other => ::std::result::Result::Err(::rapid_tlv::__private::unknown_event_type(other)),
+    }
+}
@@ -0,0 +1,263 @@
+// Generated by rapid_tlv_build. Do not edit.
+
+pub const EVT_SET: ::rapid_tlv::RapidTlvEventType = 0x10;
+pub const EVT_GET: ::rapid_tlv::RapidTlvEventType = 0x11;
+pub const EVT_BATCH: ::rapid_tlv::RapidTlvEventType = 0x20;
+
+pub const FIELD_KEY: ::rapid_tlv::RapidTlvFieldType = 0x01;
+pub const FIELD_VALUE: ::rapid_tlv::RapidTlvFieldType = 0x02;
+pub const FIELD_TTL: ::rapid_tlv::RapidTlvFieldType = 0x03;
+pub const FIELD_PERSIST: ::rapid_tlv::RapidTlvFieldType = 0x04;
+pub const FIELD_GROUP: ::rapid_tlv::RapidTlvFieldType = 0x05;
+pub const FIELD_ID: ::rapid_tlv::RapidTlvFieldType = 0x08;
+pub const FIELD_SET: ::rapid_tlv::RapidTlvFieldType = 0x09;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Set {
+    pub key: ::std::string::String,
+    pub value: ::std::vec::Vec<u8>,
+    pub ttl: ::std::option::Option<u32>,
+    pub persist: ::std::option::Option<bool>,
+    pub group: ::std::vec::Vec<::std::string::String>,
+}
+
+impl ::rapid_tlv::TlvMessage for Set {
+    const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = EVT_SET;
+
+    fn to_message(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+        let mut msg = ::rapid_tlv::RapidTlvMessage::new(EVT_SET);
+        msg.add_field(FIELD_KEY, ::rapid_tlv::TlvValue::encode_value(&self.key)?);
+        msg.add_field(FIELD_VALUE, ::rapid_tlv::TlvValue::encode_value(&self.value)?);
+        if let ::std::option::Option::Some(value) = &self.ttl {
+            msg.add_field(FIELD_TTL, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        if let ::std::option::Option::Some(value) = &self.persist {
+            msg.add_field(FIELD_PERSIST, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        for value in &self.group {
+            msg.add_repeated_field(FIELD_GROUP, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        ::std::result::Result::Ok(msg)
+    }
+
+    fn from_message(
+        msg: &::rapid_tlv::RapidTlvMessage,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        if msg.event_type != EVT_SET {
+            return ::std::result::Result::Err(
+                ::rapid_tlv::__private::unexpected_event_type(EVT_SET, msg.event_type),
+            );
+        }
+        ::std::result::Result::Ok(Self {
+            key: match msg.get_field(&FIELD_KEY) {
+                ::std::option::Option::Some(field) => <::std::string::String as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_KEY));
+                }
+            },
+            value: match msg.get_field(&FIELD_VALUE) {
+                ::std::option::Option::Some(field) => <::std::vec::Vec<u8> as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_VALUE));
+                }
+            },
+            ttl: msg
+                .get_field(&FIELD_TTL)
+                .map(|field| <u32 as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .transpose()?,
+            persist: msg
+                .get_field(&FIELD_PERSIST)
+                .map(|field| <bool as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .transpose()?,
+            group: msg
+                .get_fields(&FIELD_GROUP)
+                .map(|field| <::std::string::String as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .collect::<::std::result::Result<_, _>>()?,
+        })
+    }
+}
+
+impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for Set {
+    type Error = ::rapid_tlv::RapidTlvError;
+
+    fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+}
+
+impl ::rapid_tlv::TlvValue for Set {
+    fn encode_value(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+        ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+    }
+
+    fn decode_value(
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+
+    fn decode_field(
+        msg: &::rapid_tlv::RapidTlvMessage,
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Get {
+    pub key: ::std::string::String,
+}
+
+impl ::rapid_tlv::TlvMessage for Get {
+    const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = EVT_GET;
+
+    fn to_message(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+        let mut msg = ::rapid_tlv::RapidTlvMessage::new(EVT_GET);
+        msg.add_field(FIELD_KEY, ::rapid_tlv::TlvValue::encode_value(&self.key)?);
+        ::std::result::Result::Ok(msg)
+    }
+
+    fn from_message(
+        msg: &::rapid_tlv::RapidTlvMessage,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        if msg.event_type != EVT_GET {
+            return ::std::result::Result::Err(
+                ::rapid_tlv::__private::unexpected_event_type(EVT_GET, msg.event_type),
+            );
+        }
+        ::std::result::Result::Ok(Self {
+            key: match msg.get_field(&FIELD_KEY) {
+                ::std::option::Option::Some(field) => <::std::string::String as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_KEY));
+                }
+            },
+        })
+    }
+}
+
+impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for Get {
+    type Error = ::rapid_tlv::RapidTlvError;
+
+    fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+}
+
+impl ::rapid_tlv::TlvValue for Get {
+    fn encode_value(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+        ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+    }
+
+    fn decode_value(
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+
+    fn decode_field(
+        msg: &::rapid_tlv::RapidTlvMessage,
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Batch {
+    pub id: u64,
+    pub set: ::std::vec::Vec<Set>,
+}
+
+impl ::rapid_tlv::TlvMessage for Batch {
+    const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = EVT_BATCH;
+
+    fn to_message(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+        let mut msg = ::rapid_tlv::RapidTlvMessage::new(EVT_BATCH);
+        msg.add_field(FIELD_ID, ::rapid_tlv::TlvValue::encode_value(&self.id)?);
+        for value in &self.set {
+            msg.add_repeated_field(FIELD_SET, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        ::std::result::Result::Ok(msg)
+    }
+
+    fn from_message(
+        msg: &::rapid_tlv::RapidTlvMessage,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        if msg.event_type != EVT_BATCH {
+            return ::std::result::Result::Err(
+                ::rapid_tlv::__private::unexpected_event_type(EVT_BATCH, msg.event_type),
+            );
+        }
+        ::std::result::Result::Ok(Self {
+            id: match msg.get_field(&FIELD_ID) {
+                ::std::option::Option::Some(field) => <u64 as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_ID));
+                }
+            },
+            set: msg
+                .get_fields(&FIELD_SET)
+                .map(|field| <Set as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .collect::<::std::result::Result<_, _>>()?,
+        })
+    }
+}
+
+impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for Batch {
+    type Error = ::rapid_tlv::RapidTlvError;
+
+    fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+}
+
+impl ::rapid_tlv::TlvValue for Batch {
+    fn encode_value(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+        ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+    }
+
+    fn decode_value(
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+
+    fn decode_field(
+        msg: &::rapid_tlv::RapidTlvMessage,
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+    }
+}
+
+/// Checks that `msg` is a known event with all required fields and valid values.
+pub fn validate(
+    msg: &::rapid_tlv::RapidTlvMessage,
+) -> ::std::result::Result<(), ::rapid_tlv::RapidTlvError> {
+    match msg.event_type {
+        EVT_SET => <Set as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),
+        EVT_GET => <Get as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),
+        EVT_BATCH => <Batch as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),
+        other => ::std::result::Result::Err(::rapid_tlv::__private::unknown_event_type(other)),
+    }
+}
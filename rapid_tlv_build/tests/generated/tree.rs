@@ -0,0 +1,177 @@
+// Generated by rapid_tlv_build. Do not edit.
+
+pub const EVT_NODE: ::rapid_tlv::RapidTlvEventType = 0x10;
+pub const EVT_LINK: ::rapid_tlv::RapidTlvEventType = 0x11;
+
+pub const FIELD_NAME: ::rapid_tlv::RapidTlvFieldType = 0x01;
+pub const FIELD_NEXT: ::rapid_tlv::RapidTlvFieldType = 0x02;
+pub const FIELD_CHILDREN: ::rapid_tlv::RapidTlvFieldType = 0x03;
+pub const FIELD_PARENT: ::rapid_tlv::RapidTlvFieldType = 0x04;
+pub const FIELD_NODE: ::rapid_tlv::RapidTlvFieldType = 0x05;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Node {
+    pub name: ::std::string::String,
+    pub next: ::std::option::Option<::std::boxed::Box<Node>>,
+    pub children: ::std::vec::Vec<Node>,
+    pub parent: ::std::option::Option<::std::boxed::Box<Link>>,
+}
+
+impl ::rapid_tlv::TlvMessage for Node {
+    const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = EVT_NODE;
+
+    fn to_message(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+        let mut msg = ::rapid_tlv::RapidTlvMessage::new(EVT_NODE);
+        msg.add_field(FIELD_NAME, ::rapid_tlv::TlvValue::encode_value(&self.name)?);
+        if let ::std::option::Option::Some(value) = &self.next {
+            msg.add_field(FIELD_NEXT, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        for value in &self.children {
+            msg.add_repeated_field(FIELD_CHILDREN, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        if let ::std::option::Option::Some(value) = &self.parent {
+            msg.add_field(FIELD_PARENT, ::rapid_tlv::TlvValue::encode_value(value)?);
+        }
+        ::std::result::Result::Ok(msg)
+    }
+
+    fn from_message(
+        msg: &::rapid_tlv::RapidTlvMessage,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        if msg.event_type != EVT_NODE {
+            return ::std::result::Result::Err(
+                ::rapid_tlv::__private::unexpected_event_type(EVT_NODE, msg.event_type),
+            );
+        }
+        ::std::result::Result::Ok(Self {
+            name: match msg.get_field(&FIELD_NAME) {
+                ::std::option::Option::Some(field) => <::std::string::String as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_NAME));
+                }
+            },
+            next: msg
+                .get_field(&FIELD_NEXT)
+                .map(|field| <::std::boxed::Box<Node> as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .transpose()?,
+            children: msg
+                .get_fields(&FIELD_CHILDREN)
+                .map(|field| <Node as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .collect::<::std::result::Result<_, _>>()?,
+            parent: msg
+                .get_field(&FIELD_PARENT)
+                .map(|field| <::std::boxed::Box<Link> as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                .transpose()?,
+        })
+    }
+}
+
+impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for Node {
+    type Error = ::rapid_tlv::RapidTlvError;
+
+    fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+}
+
+impl ::rapid_tlv::TlvValue for Node {
+    fn encode_value(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+        ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+    }
+
+    fn decode_value(
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+
+    fn decode_field(
+        msg: &::rapid_tlv::RapidTlvMessage,
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct Link {
+    pub node: ::std::boxed::Box<Node>,
+}
+
+impl ::rapid_tlv::TlvMessage for Link {
+    const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = EVT_LINK;
+
+    fn to_message(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+        let mut msg = ::rapid_tlv::RapidTlvMessage::new(EVT_LINK);
+        msg.add_field(FIELD_NODE, ::rapid_tlv::TlvValue::encode_value(&self.node)?);
+        ::std::result::Result::Ok(msg)
+    }
+
+    fn from_message(
+        msg: &::rapid_tlv::RapidTlvMessage,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        if msg.event_type != EVT_LINK {
+            return ::std::result::Result::Err(
+                ::rapid_tlv::__private::unexpected_event_type(EVT_LINK, msg.event_type),
+            );
+        }
+        ::std::result::Result::Ok(Self {
+            node: match msg.get_field(&FIELD_NODE) {
+                ::std::option::Option::Some(field) => <::std::boxed::Box<Node> as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                ::std::option::Option::None => {
+                    return ::std::result::Result::Err(::rapid_tlv::__private::missing_field(FIELD_NODE));
+                }
+            },
+        })
+    }
+}
+
+impl ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for Link {
+    type Error = ::rapid_tlv::RapidTlvError;
+
+    fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+}
+
+impl ::rapid_tlv::TlvValue for Link {
+    fn encode_value(
+        &self,
+    ) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+        ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+    }
+
+    fn decode_value(
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+    }
+
+    fn decode_field(
+        msg: &::rapid_tlv::RapidTlvMessage,
+        field: &::rapid_tlv::RapidTlvField,
+    ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+        let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+        <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+    }
+}
+
+/// Checks that `msg` is a known event with all required fields and valid values.
+pub fn validate(
+    msg: &::rapid_tlv::RapidTlvMessage,
+) -> ::std::result::Result<(), ::rapid_tlv::RapidTlvError> {
+    match msg.event_type {
+        EVT_NODE => <Node as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),
+        EVT_LINK => <Link as ::rapid_tlv::TlvMessage>::from_message(msg).map(|_| ()),
+        other => ::std::result::Result::Err(::rapid_tlv::__private::unknown_event_type(other)),
+    }
+}
@@ -0,0 +1,288 @@
+//! `#[derive(TlvMessage)]` for `rapid_tlv`.
+//!
+//! ```ignore
+//! #[derive(TlvMessage)]
+//! #[tlv(event = 0x10)]
+//! struct Set {
+//!     #[tlv(field = 1)]
+//!     key: String,
+//!     #[tlv(field = 3)]
+//!     ttl: Option<u32>,
+//!     #[tlv(field = 5, repeated)]
+//!     groups: Vec<String>,
+//! }
+//! ```
+//!
+//! Every field type must implement `rapid_tlv::TlvValue`. `Option<T>` fields
+//! are optional, `repeated` fields must be a `Vec<T>` and map onto repeated
+//! TLV fields, and all other fields are required. Derived structs implement
+//! `TlvValue` themselves, so they can be nested inside other derived structs.
+
+use proc_macro::TokenStream;
+use proc_macro2::TokenStream as TokenStream2;
+use quote::quote;
+use syn::spanned::Spanned;
+use syn::{Data, DeriveInput, Expr, Fields, GenericArgument, Ident, PathArguments, Type};
+
+#[proc_macro_derive(TlvMessage, attributes(tlv))]
+pub fn derive_tlv_message(input: TokenStream) -> TokenStream {
+    let input = syn::parse_macro_input!(input as DeriveInput);
+    expand(&input)
+        .unwrap_or_else(syn::Error::into_compile_error)
+        .into()
+}
+
+enum Kind {
+    Required,
+    Optional(Type),
+    Repeated(Type),
+}
+
+struct TlvField {
+    ident: Ident,
+    ty: Type,
+    field_type: Expr,
+    kind: Kind,
+}
+
+fn expand(input: &DeriveInput) -> syn::Result<TokenStream2> {
+    let event = parse_event(input)?;
+
+    let named = match &input.data {
+        Data::Struct(data) => match &data.fields {
+            Fields::Named(named) => &named.named,
+            _ => {
+                return Err(syn::Error::new(
+                    input.ident.span(),
+                    "TlvMessage requires a struct with named fields",
+                ));
+            }
+        },
+        _ => {
+            return Err(syn::Error::new(
+                input.ident.span(),
+                "TlvMessage can only be derived for structs",
+            ));
+        }
+    };
+
+    let mut fields = Vec::new();
+    for field in named {
+        fields.push(parse_field(field)?);
+    }
+    check_duplicates(&fields)?;
+
+    let name = &input.ident;
+    let (impl_generics, ty_generics, where_clause) = input.generics.split_for_impl();
+
+    let encode = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let field_type = &f.field_type;
+        match &f.kind {
+            Kind::Required => quote! {
+                msg.add_field(#field_type, ::rapid_tlv::TlvValue::encode_value(&self.#ident)?);
+            },
+            Kind::Optional(_) => quote! {
+                if let ::std::option::Option::Some(value) = &self.#ident {
+                    msg.add_field(#field_type, ::rapid_tlv::TlvValue::encode_value(value)?);
+                }
+            },
+            Kind::Repeated(_) => quote! {
+                for value in &self.#ident {
+                    msg.add_repeated_field(#field_type, ::rapid_tlv::TlvValue::encode_value(value)?);
+                }
+            },
+        }
+    });
+
+    let decode = fields.iter().map(|f| {
+        let ident = &f.ident;
+        let ty = &f.ty;
+        let field_type = &f.field_type;
+        match &f.kind {
+            Kind::Required => quote! {
+                #ident: match msg.get_field(&#field_type) {
+                    ::std::option::Option::Some(field) => {
+                        <#ty as ::rapid_tlv::TlvValue>::decode_field(msg, field)?
+                    }
+                    ::std::option::Option::None => {
+                        return ::std::result::Result::Err(
+                            ::rapid_tlv::__private::missing_field(#field_type),
+                        );
+                    }
+                }
+            },
+            Kind::Optional(inner) => quote! {
+                #ident: match msg.get_field(&#field_type) {
+                    ::std::option::Option::Some(field) => ::std::option::Option::Some(
+                        <#inner as ::rapid_tlv::TlvValue>::decode_field(msg, field)?,
+                    ),
+                    ::std::option::Option::None => ::std::option::Option::None,
+                }
+            },
+            Kind::Repeated(inner) => quote! {
+                #ident: msg
+                    .get_fields(&#field_type)
+                    .map(|field| <#inner as ::rapid_tlv::TlvValue>::decode_field(msg, field))
+                    .collect::<::std::result::Result<::std::vec::Vec<#inner>, ::rapid_tlv::RapidTlvError>>()?
+            },
+        }
+    });
+
+    Ok(quote! {
+        impl #impl_generics ::rapid_tlv::TlvMessage for #name #ty_generics #where_clause {
+            const EVENT_TYPE: ::rapid_tlv::RapidTlvEventType = #event;
+
+            fn to_message(&self) -> ::std::result::Result<::rapid_tlv::RapidTlvMessage, ::rapid_tlv::RapidTlvError> {
+                let mut msg = ::rapid_tlv::RapidTlvMessage::new(#event);
+                #(#encode)*
+                ::std::result::Result::Ok(msg)
+            }
+
+            fn from_message(msg: &::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+                if msg.event_type != #event {
+                    return ::std::result::Result::Err(
+                        ::rapid_tlv::__private::unexpected_event_type(#event, msg.event_type),
+                    );
+                }
+                ::std::result::Result::Ok(Self {
+                    #(#decode,)*
+                })
+            }
+        }
+
+        impl #impl_generics ::std::convert::TryFrom<::rapid_tlv::RapidTlvMessage> for #name #ty_generics #where_clause {
+            type Error = ::rapid_tlv::RapidTlvError;
+
+            fn try_from(msg: ::rapid_tlv::RapidTlvMessage) -> ::std::result::Result<Self, Self::Error> {
+                <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+            }
+        }
+
+        impl #impl_generics ::rapid_tlv::TlvValue for #name #ty_generics #where_clause {
+            fn encode_value(&self) -> ::std::result::Result<::rapid_tlv::__private::Bytes, ::rapid_tlv::RapidTlvError> {
+                ::rapid_tlv::TlvMessage::to_message(self)?.to_bytes()
+            }
+
+            fn decode_value(field: &::rapid_tlv::RapidTlvField) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+                let msg = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_value(field)?;
+                <Self as ::rapid_tlv::TlvMessage>::from_message(&msg)
+            }
+
+            fn decode_field(
+                msg: &::rapid_tlv::RapidTlvMessage,
+                field: &::rapid_tlv::RapidTlvField,
+            ) -> ::std::result::Result<Self, ::rapid_tlv::RapidTlvError> {
+                let nested = <::rapid_tlv::RapidTlvMessage as ::rapid_tlv::TlvValue>::decode_field(msg, field)?;
+                <Self as ::rapid_tlv::TlvMessage>::from_message(&nested)
+            }
+        }
+    })
+}
+
+fn parse_event(input: &DeriveInput) -> syn::Result<Expr> {
+    let mut event = None;
+    for attr in input.attrs.iter().filter(|a| a.path().is_ident("tlv")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("event") {
+                event = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            } else {
+                Err(meta.error("unknown tlv attribute, expected `event`"))
+            }
+        })?;
+    }
+    event.ok_or_else(|| {
+        syn::Error::new(
+            input.ident.span(),
+            "missing `#[tlv(event = ...)]` attribute",
+        )
+    })
+}
+
+fn parse_field(field: &syn::Field) -> syn::Result<TlvField> {
+    let ident = field.ident.clone().unwrap();
+    let mut field_type = None;
+    let mut repeated = false;
+
+    for attr in field.attrs.iter().filter(|a| a.path().is_ident("tlv")) {
+        attr.parse_nested_meta(|meta| {
+            if meta.path.is_ident("field") {
+                field_type = Some(meta.value()?.parse::<Expr>()?);
+                Ok(())
+            } else if meta.path.is_ident("repeated") {
+                repeated = true;
+                Ok(())
+            } else {
+                Err(meta.error("unknown tlv attribute, expected `field` or `repeated`"))
+            }
+        })?;
+    }
+
+    let field_type = field_type
+        .ok_or_else(|| syn::Error::new(ident.span(), "missing `#[tlv(field = ...)]` attribute"))?;
+
+    let kind = if repeated {
+        match generic_argument(&field.ty, "Vec") {
+            Some(inner) => Kind::Repeated(inner),
+            None => {
+                return Err(syn::Error::new(
+                    field.ty.span(),
+                    "repeated fields must have type `Vec<T>`",
+                ));
+            }
+        }
+    } else {
+        match generic_argument(&field.ty, "Option") {
+            Some(inner) => Kind::Optional(inner),
+            None => Kind::Required,
+        }
+    };
+
+    Ok(TlvField {
+        ident,
+        ty: field.ty.clone(),
+        field_type,
+        kind,
+    })
+}
+
+// Returns `T` if `ty` is written as `wrapper<T>`
+fn generic_argument(ty: &Type, wrapper: &str) -> Option<Type> {
+    let Type::Path(path) = ty else {
+        return None;
+    };
+    let segment = path.path.segments.last()?;
+    if segment.ident != wrapper {
+        return None;
+    }
+    let PathArguments::AngleBracketed(args) = &segment.arguments else {
+        return None;
+    };
+    match args.args.first()? {
+        GenericArgument::Type(inner) if args.args.len() == 1 => Some(inner.clone()),
+        _ => None,
+    }
+}
+
+// Literal field numbers can be checked for collisions at compile time
+fn check_duplicates(fields: &[TlvField]) -> syn::Result<()> {
+    let mut seen = Vec::new();
+    for field in fields {
+        if let Expr::Lit(syn::ExprLit {
+            lit: syn::Lit::Int(lit),
+            ..
+        }) = &field.field_type
+        {
+            let value = lit.base10_parse::<u16>()?;
+            if seen.contains(&value) {
+                return Err(syn::Error::new(
+                    lit.span(),
+                    format!("field type {value} is used more than once"),
+                ));
+            }
+            seen.push(value);
+        }
+    }
+    Ok(())
+}
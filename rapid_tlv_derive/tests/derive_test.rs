@@ -0,0 +1,140 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvMessage, TlvMessage};
+
+pub const EVT_SET: u8 = 0x10;
+pub const EVT_BATCH: u8 = 0x20;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_TTL: u16 = 0x03;
+pub const FIELD_PERSIST: u16 = 0x04;
+pub const FIELD_GROUP: u16 = 0x05;
+
+#[derive(Debug, Clone, PartialEq, TlvMessage)]
+#[tlv(event = EVT_SET)]
+struct Set {
+    #[tlv(field = FIELD_KEY)]
+    key: String,
+    #[tlv(field = FIELD_VALUE)]
+    value: Vec<u8>,
+    #[tlv(field = FIELD_TTL)]
+    ttl: Option<u32>,
+    #[tlv(field = FIELD_PERSIST)]
+    persist: bool,
+    #[tlv(field = FIELD_GROUP, repeated)]
+    groups: Vec<String>,
+}
+
+#[derive(Debug, PartialEq, TlvMessage)]
+#[tlv(event = 0x20)]
+struct Batch {
+    #[tlv(field = 1, repeated)]
+    sets: Vec<Set>,
+    #[tlv(field = 2)]
+    id: u64,
+}
+
+fn sample() -> Set {
+    Set {
+        key: "test_key".into(),
+        value: b"test_value".to_vec(),
+        ttl: Some(60),
+        persist: true,
+        groups: vec!["a".into(), "b".into()],
+    }
+}
+
+#[test]
+fn test_derive_roundtrip() {
+    let set = sample();
+    let mut msg = set.clone().into_message().unwrap();
+
+    assert_eq!(msg.event_type, EVT_SET);
+    assert_eq!(msg.get_field(&FIELD_TTL).unwrap().value(), &[0, 0, 0, 60]);
+    assert_eq!(msg.get_fields(&FIELD_GROUP).count(), 2);
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(Set::try_from(parsed).unwrap(), set);
+
+    // Absent optional fields stay absent
+    let no_ttl = Set {
+        ttl: None,
+        ..sample()
+    };
+    let msg = no_ttl.to_message().unwrap();
+    assert!(msg.get_field(&FIELD_TTL).is_none());
+    assert_eq!(Set::from_message(&msg).unwrap(), no_ttl);
+}
+
+#[test]
+fn test_derive_nested() {
+    let batch = Batch {
+        sets: vec![
+            sample(),
+            Set {
+                key: "other".into(),
+                ..sample()
+            },
+        ],
+        id: 7,
+    };
+    assert_eq!(Batch::EVENT_TYPE, EVT_BATCH);
+
+    let mut msg = batch.to_message().unwrap();
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(Batch::try_from(parsed).unwrap(), batch);
+}
+
+#[test]
+fn test_derive_errors() {
+    // Missing required field
+    let mut msg = sample().to_message().unwrap();
+    msg.remove_field(FIELD_KEY);
+    let err = Set::from_message(&msg).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_KEY));
+
+    // Value with the wrong width
+    let mut msg = sample().to_message().unwrap();
+    msg.add_field(FIELD_TTL, Bytes::from_static(&[0, 60]));
+    let err = Set::from_message(&msg).unwrap_err();
+    assert_eq!(err.field_type(), Some(FIELD_TTL));
+
+    // Wrong event type
+    let err = Set::from_message(&RapidTlvMessage::new(EVT_BATCH)).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::InvalidEventType);
+}
+
+#[derive(Debug, PartialEq, TlvMessage)]
+#[tlv(event = 0x30)]
+struct Node {
+    #[tlv(field = 1, repeated)]
+    children: Vec<Node>,
+}
+
+#[test]
+fn test_derive_recursive_type_respects_nesting_depth() {
+    // A chain of 200000 nested nodes, written from the outside in
+    let levels = 200_000u32;
+    let mut raw = Vec::with_capacity(5 + 10 * levels as usize);
+    for level in (1..=levels).rev() {
+        raw.extend_from_slice(&(5 + 10 * level).to_be_bytes());
+        raw.push(0x30);
+        raw.push(1);
+        raw.extend_from_slice(&(5 + 10 * (level - 1)).to_be_bytes());
+    }
+    raw.extend_from_slice(&5u32.to_be_bytes());
+    raw.push(0x30);
+
+    let msg = RapidTlvMessage::parse(Bytes::from(raw)).unwrap();
+    let err = Node::from_message(&msg).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+
+    let shallow = Node {
+        children: vec![Node {
+            children: vec![Node { children: vec![] }],
+        }],
+    };
+    let parsed = RapidTlvMessage::parse(shallow.to_message().unwrap().to_bytes().unwrap()).unwrap();
+    assert_eq!(Node::from_message(&parsed).unwrap(), shallow);
+}
@@ -0,0 +1,178 @@
+use crate::error::{Error, ErrorCode};
+use crate::format::WireFormat;
+use crate::message::Message;
+use crate::wire::WireConfig;
+use bytes::{BufMut, Bytes, BytesMut};
+use hmac::{Hmac, Mac};
+use sha2::Sha256;
+use std::collections::HashMap;
+use std::fmt;
+
+/// Length of the HMAC-SHA256 tag at the end of an authenticated frame.
+pub const AUTH_TAG_LEN: usize = 32;
+
+// Key id and tag
+const TRAILER_LEN: usize = 1 + AUTH_TAG_LEN;
+
+type HmacSha256 = Hmac<Sha256>;
+
+/// Shared secrets for authenticated frames, identified by a one-byte key id.
+///
+/// Frames are signed with the current key and verified with whichever key
+/// their id names, so keys can be rotated without dropping messages: add the
+/// new key everywhere, make it current on the senders, then remove the old
+/// one.
+#[derive(Clone, Default)]
+pub struct Keyring {
+    keys: HashMap<u8, Vec<u8>>,
+    current: Option<u8>,
+}
+
+impl Keyring {
+    pub fn new() -> Keyring {
+        Keyring::default()
+    }
+
+    pub fn add_key(&mut self, key_id: u8, key: impl Into<Vec<u8>>) {
+        self.keys.insert(key_id, key.into());
+    }
+
+    pub fn with_key(mut self, key_id: u8, key: impl Into<Vec<u8>>) -> Keyring {
+        self.add_key(key_id, key);
+        self
+    }
+
+    /// Removes a key; frames signed with it no longer verify.
+    pub fn remove_key(&mut self, key_id: u8) -> bool {
+        if self.current == Some(key_id) {
+            self.current = None;
+        }
+        self.keys.remove(&key_id).is_some()
+    }
+
+    pub fn contains_key(&self, key_id: u8) -> bool {
+        self.keys.contains_key(&key_id)
+    }
+
+    /// Selects the key that new frames are signed with.
+    pub fn set_current(&mut self, key_id: u8) {
+        self.current = Some(key_id);
+    }
+
+    pub fn with_current(mut self, key_id: u8) -> Keyring {
+        self.set_current(key_id);
+        self
+    }
+
+    pub fn current(&self) -> Option<u8> {
+        self.current
+    }
+
+    fn mac(&self, key_id: u8) -> Option<HmacSha256> {
+        let key = self.keys.get(&key_id)?;
+        Some(HmacSha256::new_from_slice(key).expect("HMAC takes keys of any length"))
+    }
+
+    /// Encodes `msg` as described by `config` and signs the frame.
+    pub(crate) fn encode(&self, msg: &Message, config: &WireConfig) -> Result<Bytes, Error> {
+        self.sign(&config.encode(msg)?, config.format())
+    }
+
+    /// Verifies a frame written by `encode` with the same `config` and
+    /// parses it.
+    pub(crate) fn parse(&self, raw: Bytes, config: &WireConfig) -> Result<Message, Error> {
+        config.parse(self.verify(&raw, config.format())?)
+    }
+
+    /// Appends the current key id and a tag over everything before it to
+    /// `frame`, counting both in its length prefix.
+    pub(crate) fn sign(&self, frame: &[u8], format: &WireFormat) -> Result<Bytes, Error> {
+        let Some((key_id, mut mac)) = self
+            .current
+            .and_then(|id| self.mac(id).map(|mac| (id, mac)))
+        else {
+            return Err(Error::new(
+                ErrorCode::ConfigInvalid,
+                "Keyring has no current key to sign with".into(),
+            ));
+        };
+        // The frame was just encoded, so its prefix is complete and valid
+        let (_, prefix_len) = format.read_prefix(frame)?.expect("complete frame");
+        let body = &frame[prefix_len..];
+        let body_len = body.len() + TRAILER_LEN;
+
+        let mut buffer = BytesMut::with_capacity(format.frame_len(body_len));
+        format.put_prefix(&mut buffer, body_len)?;
+        buffer.put_slice(body);
+        buffer.put_u8(key_id);
+        mac.update(&buffer);
+        buffer.put_slice(&mac.finalize().into_bytes());
+        Ok(buffer.freeze())
+    }
+
+    /// Checks the tag of an authenticated frame and returns the frame
+    /// without it.
+    pub(crate) fn verify(&self, raw: &[u8], format: &WireFormat) -> Result<Bytes, Error> {
+        let Some((declared, prefix_len)) = format.read_prefix(raw).map_err(|e| e.at_offset(0))?
+        else {
+            return Err(short_frame());
+        };
+        if declared != raw.len() {
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                "Malformed tlv field: Declared length doesn't match actual length".into(),
+            )
+            .at_offset(0));
+        }
+        if raw.len() < prefix_len + 1 + TRAILER_LEN {
+            return Err(short_frame());
+        }
+
+        let tag_start = raw.len() - AUTH_TAG_LEN;
+        let key_id = raw[tag_start - 1];
+        let Some(mut mac) = self.mac(key_id) else {
+            crate::rapid_warn!("Frame authentication failed: Unknown key id {}", key_id);
+            return Err(Error::new(
+                ErrorCode::AuthenticationFailed,
+                format!("Unknown key id {key_id}"),
+            )
+            .at_offset(tag_start - 1));
+        };
+        mac.update(&raw[..tag_start]);
+        // verify_slice compares in constant time
+        if mac.verify_slice(&raw[tag_start..]).is_err() {
+            crate::rapid_warn!("Frame authentication failed: Tag mismatch");
+            return Err(Error::new(
+                ErrorCode::AuthenticationFailed,
+                "Authentication tag mismatch".into(),
+            )
+            .at_offset(tag_start));
+        }
+
+        let body = &raw[prefix_len..raw.len() - TRAILER_LEN];
+        let mut frame = BytesMut::with_capacity(format.frame_len(body.len()));
+        format.put_prefix(&mut frame, body.len())?;
+        frame.put_slice(body);
+        Ok(frame.freeze())
+    }
+}
+
+fn short_frame() -> Error {
+    Error::new(
+        ErrorCode::Malformed,
+        "Not enough data for authenticated TLV frame".into(),
+    )
+    .at_offset(0)
+}
+
+// Keys are secret; only their ids are shown
+impl fmt::Debug for Keyring {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut ids: Vec<_> = self.keys.keys().collect();
+        ids.sort();
+        f.debug_struct("Keyring")
+            .field("key_ids", &ids)
+            .field("current", &self.current)
+            .finish()
+    }
+}
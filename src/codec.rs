@@ -0,0 +1,102 @@
+#[cfg(feature = "auth")]
+use crate::auth::Keyring;
+use crate::decoder::split_frame;
+use crate::error::Error;
+use crate::limits::ParseLimits;
+use crate::message::Message;
+use crate::wire::WireConfig;
+use bytes::BytesMut;
+use tokio_util::codec::{Decoder, Encoder};
+
+/// `tokio_util` codec for length-prefixed TLV frames, for use with `Framed`.
+///
+/// The maximum frame size is the one in the config's `ParseLimits`, which
+/// bounds both decoded and encoded frames.
+#[derive(Debug, Clone)]
+pub struct TlvCodec {
+    config: WireConfig,
+    #[cfg(feature = "auth")]
+    keyring: Option<Keyring>,
+}
+
+impl TlvCodec {
+    pub fn new() -> TlvCodec {
+        TlvCodec {
+            config: WireConfig::default(),
+            #[cfg(feature = "auth")]
+            keyring: None,
+        }
+    }
+
+    /// A codec for frames of up to `max_frame_size` bytes, whose fields may
+    /// be just as large.
+    pub fn with_max_frame_size(max_frame_size: usize) -> TlvCodec {
+        let limits = ParseLimits::default()
+            .with_max_frame_size(max_frame_size)
+            .with_max_field_size(max_frame_size);
+        TlvCodec::new().with_config(WireConfig::default().with_limits(limits))
+    }
+
+    /// Encodes and decodes frames laid out as described by `config`,
+    /// including its limits.
+    pub fn with_config(mut self, config: WireConfig) -> TlvCodec {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &WireConfig {
+        &self.config
+    }
+
+    /// Signs encoded frames with the keyring's current key and rejects
+    /// decoded frames whose tag does not verify.
+    #[cfg(feature = "auth")]
+    pub fn with_keyring(mut self, keyring: Keyring) -> TlvCodec {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    pub fn max_frame_size(&self) -> usize {
+        self.config.limits().max_frame_size()
+    }
+}
+
+impl Default for TlvCodec {
+    fn default() -> Self {
+        TlvCodec::new()
+    }
+}
+
+impl Decoder for TlvCodec {
+    type Item = Message;
+    type Error = Error;
+
+    fn decode(&mut self, src: &mut BytesMut) -> Result<Option<Message>, Error> {
+        // Oversized frames are rejected before split_frame reserves room for them
+        let Some(frame) = split_frame(src, &self.config)? else {
+            return Ok(None);
+        };
+        #[cfg(feature = "auth")]
+        if let Some(keyring) = &self.keyring {
+            return keyring.parse(frame, &self.config).map(Some);
+        }
+        self.config.parse(frame).map(Some)
+    }
+}
+
+impl Encoder<Message> for TlvCodec {
+    type Error = Error;
+
+    fn encode(&mut self, item: Message, dst: &mut BytesMut) -> Result<(), Error> {
+        #[cfg(feature = "auth")]
+        let encoded = match &self.keyring {
+            Some(keyring) => keyring.encode(&item, &self.config)?,
+            None => self.config.encode(&item)?,
+        };
+        #[cfg(not(feature = "auth"))]
+        let encoded = self.config.encode(&item)?;
+        self.config.limits().check_frame_size(encoded.len())?;
+        dst.extend_from_slice(&encoded);
+        Ok(())
+    }
+}
@@ -0,0 +1,85 @@
+use crate::error::{Error, ErrorCode};
+
+/// Fields sections shorter than this are sent uncompressed.
+pub const DEFAULT_COMPRESSION_THRESHOLD: usize = 1024;
+/// Largest fields section a receiver decompresses by default.
+pub const DEFAULT_MAX_DECOMPRESSED_SIZE: usize = 16 * 1024 * 1024;
+
+/// Algorithm used to compress the fields section of a frame.
+///
+/// Each algorithm is behind the cargo feature of the same name. A receiver
+/// built without it rejects frames compressed with it as `Malformed`.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Compression {
+    #[cfg(feature = "lz4")]
+    Lz4,
+    /// Zstandard at the given level; 0 selects the library default
+    #[cfg(feature = "zstd")]
+    Zstd(i32),
+}
+
+#[cfg(feature = "lz4")]
+const ID_LZ4: u8 = 1;
+#[cfg(feature = "zstd")]
+const ID_ZSTD: u8 = 2;
+
+impl Compression {
+    /// Whether this build can decompress at least one algorithm.
+    pub(crate) const SUPPORTED: bool = cfg!(any(feature = "lz4", feature = "zstd"));
+
+    /// The algorithm byte written before the compressed data.
+    pub(crate) fn id(self) -> u8 {
+        match self {
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => ID_LZ4,
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(_) => ID_ZSTD,
+        }
+    }
+
+    #[cfg_attr(not(any(feature = "lz4", feature = "zstd")), allow(unused_variables))]
+    pub(crate) fn compress(self, data: &[u8]) -> Result<Vec<u8>, Error> {
+        match self {
+            #[cfg(feature = "lz4")]
+            Compression::Lz4 => Ok(lz4_flex::block::compress(data)),
+            #[cfg(feature = "zstd")]
+            Compression::Zstd(level) => zstd::bulk::compress(data, level).map_err(|e| {
+                Error::new(
+                    ErrorCode::InternalServerError,
+                    format!("Compression failed: {e}"),
+                )
+            }),
+        }
+    }
+}
+
+/// Decompresses data written by `Compression::compress` with the algorithm
+/// `id` into exactly `len` bytes.
+#[cfg_attr(not(any(feature = "lz4", feature = "zstd")), allow(unused_variables))]
+pub(crate) fn decompress(id: u8, data: &[u8], len: usize) -> Result<Vec<u8>, Error> {
+    let decompressed: Vec<u8> = match id {
+        #[cfg(feature = "lz4")]
+        ID_LZ4 => lz4_flex::block::decompress(data, len).map_err(decompression_failed),
+        #[cfg(feature = "zstd")]
+        ID_ZSTD => zstd::bulk::decompress(data, len).map_err(decompression_failed),
+        _ => Err(Error::new(
+            ErrorCode::Malformed,
+            format!("Unsupported compression algorithm {id}"),
+        )),
+    }?;
+    if decompressed.len() != len {
+        return Err(Error::new(
+            ErrorCode::Malformed,
+            format!(
+                "Decompressed {} bytes, but the frame declares {len}",
+                decompressed.len()
+            ),
+        ));
+    }
+    Ok(decompressed)
+}
+
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+fn decompression_failed(err: impl std::fmt::Display) -> Error {
+    Error::new(ErrorCode::Malformed, format!("Decompression failed: {err}"))
+}
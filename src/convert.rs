@@ -0,0 +1,154 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::{Field, FieldType};
+use crate::message::{EventType, Message};
+use bytes::Bytes;
+
+/// A Rust value that can be stored in a single field.
+///
+/// Integers and floats use the protocol's big-endian encoding, strings are UTF-8
+/// and byte buffers are stored as-is.
+pub trait TlvValue: Sized {
+    fn encode_value(&self) -> Result<Bytes, Error>;
+
+    fn decode_value(field: &Field) -> Result<Self, Error>;
+
+    /// Decodes a field read from `msg`. Nested messages are parsed through
+    /// `msg`, so they count towards its nesting depth and inherit its limits.
+    fn decode_field(msg: &Message, field: &Field) -> Result<Self, Error> {
+        let _ = msg;
+        Self::decode_value(field)
+    }
+}
+
+/// A Rust type that maps onto one event type and its fields.
+///
+/// Usually implemented with `#[derive(TlvMessage)]`.
+pub trait TlvMessage: Sized {
+    const EVENT_TYPE: EventType;
+
+    fn to_message(&self) -> Result<Message, Error>;
+
+    fn from_message(msg: &Message) -> Result<Self, Error>;
+
+    fn into_message(self) -> Result<Message, Error> {
+        self.to_message()
+    }
+}
+
+/// Error for a required field that is absent from a message.
+pub fn missing_field(field_type: FieldType) -> Error {
+    Error::new(
+        ErrorCode::Malformed,
+        format!("Missing required field type {field_type}"),
+    )
+    .with_field_type(field_type)
+}
+
+/// Error for a message whose event type does not match the target type.
+pub fn unexpected_event_type(expected: EventType, actual: EventType) -> Error {
+    Error::new(
+        ErrorCode::InvalidEventType,
+        format!("Expected event type {expected}, got {actual}"),
+    )
+}
+
+/// Error for a message whose event type is not known at all.
+pub fn unknown_event_type(event_type: EventType) -> Error {
+    Error::new(
+        ErrorCode::InvalidEventType,
+        format!("Unknown event type {event_type}"),
+    )
+}
+
+macro_rules! int_value {
+    ($($ty:ty, $get:ident;)*) => {
+        $(
+            impl TlvValue for $ty {
+                fn encode_value(&self) -> Result<Bytes, Error> {
+                    Ok(Bytes::copy_from_slice(&self.to_be_bytes()))
+                }
+
+                fn decode_value(field: &Field) -> Result<Self, Error> {
+                    field.$get()
+                }
+            }
+        )*
+    };
+}
+
+int_value! {
+    u8, get_u8;
+    u16, get_u16;
+    u32, get_u32;
+    u64, get_u64;
+    i64, get_i64;
+    f64, get_f64;
+}
+
+impl TlvValue for bool {
+    fn encode_value(&self) -> Result<Bytes, Error> {
+        Ok(Bytes::copy_from_slice(&[*self as u8]))
+    }
+
+    fn decode_value(field: &Field) -> Result<Self, Error> {
+        field.get_bool()
+    }
+}
+
+impl TlvValue for String {
+    fn encode_value(&self) -> Result<Bytes, Error> {
+        Ok(Bytes::copy_from_slice(self.as_bytes()))
+    }
+
+    fn decode_value(field: &Field) -> Result<Self, Error> {
+        field.get_str().map(str::to_string)
+    }
+}
+
+impl TlvValue for Vec<u8> {
+    fn encode_value(&self) -> Result<Bytes, Error> {
+        Ok(Bytes::copy_from_slice(self))
+    }
+
+    fn decode_value(field: &Field) -> Result<Self, Error> {
+        Ok(field.value().to_vec())
+    }
+}
+
+impl TlvValue for Bytes {
+    fn encode_value(&self) -> Result<Bytes, Error> {
+        Ok(self.clone())
+    }
+
+    fn decode_value(field: &Field) -> Result<Self, Error> {
+        Ok(field.bytes().clone())
+    }
+}
+
+impl TlvValue for Message {
+    fn encode_value(&self) -> Result<Bytes, Error> {
+        self.to_bytes()
+    }
+
+    fn decode_value(field: &Field) -> Result<Self, Error> {
+        Message::parse(field.bytes().clone()).map_err(|e| e.with_field_type(*field.field_type()))
+    }
+
+    fn decode_field(msg: &Message, field: &Field) -> Result<Self, Error> {
+        msg.parse_nested(field)
+    }
+}
+
+impl<T: TlvValue> TlvValue for Box<T> {
+    fn encode_value(&self) -> Result<Bytes, Error> {
+        (**self).encode_value()
+    }
+
+    fn decode_value(field: &Field) -> Result<Self, Error> {
+        T::decode_value(field).map(Box::new)
+    }
+
+    fn decode_field(msg: &Message, field: &Field) -> Result<Self, Error> {
+        T::decode_field(msg, field).map(Box::new)
+    }
+}
@@ -0,0 +1,485 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::FieldType;
+use crate::limits::ParseLimits;
+use crate::message::Message;
+use crate::message_ref::MessageRef;
+use crate::ser::FieldNumbers;
+use serde::Deserialize;
+use serde::de::value::{StrDeserializer, U32Deserializer};
+use serde::de::{self, DeserializeSeed, IntoDeserializer, Visitor};
+use std::fmt::Display;
+
+/// Deserializes a struct from the fields of `msg`.
+///
+/// Uses the same field numbering and value encodings as `to_message`. The
+/// event type is not checked. Strings and byte buffers can be borrowed from
+/// the message. Nested structs are parsed within the message's `ParseLimits`.
+pub fn from_message<'de, T: Deserialize<'de>>(msg: &'de Message) -> Result<T, Error> {
+    T::deserialize(MessageDeserializer {
+        source: Source::Owned(msg),
+        depth: msg.depth(),
+        limits: *msg.limits(),
+    })
+}
+
+impl de::Error for Error {
+    fn custom<T: Display>(msg: T) -> Error {
+        Error::new(ErrorCode::Malformed, msg.to_string())
+    }
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::new(
+        ErrorCode::Malformed,
+        format!("{what} cannot be read from a TLV message"),
+    )
+}
+
+// Top-level messages are owned; nested ones are borrowed views into a field value
+#[derive(Clone, Copy)]
+enum Source<'de> {
+    Owned(&'de Message),
+    Borrowed(MessageRef<'de>),
+}
+
+impl<'de> Source<'de> {
+    fn last(&self, field_type: FieldType) -> Option<&'de [u8]> {
+        match self {
+            Source::Owned(msg) => msg.get_field(&field_type).map(|f| f.value()),
+            Source::Borrowed(view) => view.get_field(field_type),
+        }
+    }
+
+    fn all(&self, field_type: FieldType) -> Vec<&'de [u8]> {
+        match self {
+            Source::Owned(msg) => msg.get_fields(&field_type).map(|f| f.value()).collect(),
+            Source::Borrowed(view) => view.get_fields(field_type).collect(),
+        }
+    }
+}
+
+struct MessageDeserializer<'de> {
+    source: Source<'de>,
+    depth: u8,
+    limits: ParseLimits,
+}
+
+impl<'de> de::Deserializer<'de> for MessageDeserializer<'de> {
+    type Error = Error;
+
+    fn deserialize_any<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_map(StructAccess {
+            source: self.source,
+            depth: self.depth,
+            limits: self.limits,
+            fields,
+            numbers: FieldNumbers::default(),
+            pending: 0,
+        })
+    }
+
+    serde::forward_to_deserialize_any! {
+        bool i8 i16 i32 i64 i128 u8 u16 u32 u64 u128 f32 f64 char str string
+        bytes byte_buf option unit unit_struct seq tuple tuple_struct map enum
+        identifier ignored_any
+    }
+}
+
+struct StructAccess<'de> {
+    source: Source<'de>,
+    depth: u8,
+    limits: ParseLimits,
+    fields: &'static [&'static str],
+    numbers: FieldNumbers,
+    pending: FieldType,
+}
+
+impl<'de> de::MapAccess<'de> for StructAccess<'de> {
+    type Error = Error;
+
+    fn next_key_seed<K: DeserializeSeed<'de>>(
+        &mut self,
+        seed: K,
+    ) -> Result<Option<K::Value>, Error> {
+        let Some(name) = self.fields.get(self.numbers.index()) else {
+            return Ok(None);
+        };
+        self.pending = self.numbers.next(name)?;
+
+        let key: StrDeserializer<'_, Error> = name.into_deserializer();
+        seed.deserialize(key).map(Some)
+    }
+
+    fn next_value_seed<V: DeserializeSeed<'de>>(&mut self, seed: V) -> Result<V::Value, Error> {
+        seed.deserialize(ValueDeserializer {
+            source: self.source,
+            depth: self.depth,
+            limits: self.limits,
+            field_type: self.pending,
+        })
+    }
+}
+
+// All occurrences of one field type: options and sequences look at every
+// occurrence, scalars at the last one
+struct ValueDeserializer<'de> {
+    source: Source<'de>,
+    depth: u8,
+    limits: ParseLimits,
+    field_type: FieldType,
+}
+
+impl<'de> ValueDeserializer<'de> {
+    fn last(&self) -> Result<FieldDeserializer<'de>, Error> {
+        match self.source.last(self.field_type) {
+            Some(value) => Ok(FieldDeserializer {
+                value,
+                field_type: self.field_type,
+                depth: self.depth,
+                limits: self.limits,
+            }),
+            None => Err(crate::convert::missing_field(self.field_type)),
+        }
+    }
+}
+
+macro_rules! forward_to_last {
+    ($($method:ident),*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                self.last()?.$method(visitor)
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for ValueDeserializer<'de> {
+    type Error = Error;
+
+    forward_to_last!(
+        deserialize_any,
+        deserialize_bool,
+        deserialize_i8,
+        deserialize_i16,
+        deserialize_i32,
+        deserialize_i64,
+        deserialize_u8,
+        deserialize_u16,
+        deserialize_u32,
+        deserialize_u64,
+        deserialize_f32,
+        deserialize_f64,
+        deserialize_char,
+        deserialize_str,
+        deserialize_string,
+        deserialize_bytes,
+        deserialize_byte_buf,
+        deserialize_unit,
+        deserialize_map,
+        deserialize_identifier
+    );
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.source.last(self.field_type) {
+            Some(_) => visitor.visit_some(self),
+            None => visitor.visit_none(),
+        }
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let values = self.source.all(self.field_type);
+        visitor.visit_seq(RepeatedAccess {
+            values: values.into_iter(),
+            field_type: self.field_type,
+            depth: self.depth,
+            limits: self.limits,
+        })
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.last()?.deserialize_unit_struct(name, visitor)
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, _: V) -> Result<V::Value, Error> {
+        Err(unsupported("Tuple"))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        _: V,
+    ) -> Result<V::Value, Error> {
+        Err(unsupported("Tuple struct"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.last()?.deserialize_struct(name, fields, visitor)
+    }
+
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        variants: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        self.last()?.deserialize_enum(name, variants, visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
+
+struct RepeatedAccess<'de> {
+    values: std::vec::IntoIter<&'de [u8]>,
+    field_type: FieldType,
+    depth: u8,
+    limits: ParseLimits,
+}
+
+impl<'de> de::SeqAccess<'de> for RepeatedAccess<'de> {
+    type Error = Error;
+
+    fn next_element_seed<T: DeserializeSeed<'de>>(
+        &mut self,
+        seed: T,
+    ) -> Result<Option<T::Value>, Error> {
+        match self.values.next() {
+            Some(value) => seed
+                .deserialize(FieldDeserializer {
+                    value,
+                    field_type: self.field_type,
+                    depth: self.depth,
+                    limits: self.limits,
+                })
+                .map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn size_hint(&self) -> Option<usize> {
+        Some(self.values.len())
+    }
+}
+
+// A single field value
+struct FieldDeserializer<'de> {
+    value: &'de [u8],
+    field_type: FieldType,
+    depth: u8,
+    limits: ParseLimits,
+}
+
+impl<'de> FieldDeserializer<'de> {
+    fn fixed<const N: usize>(&self) -> Result<[u8; N], Error> {
+        self.value.try_into().map_err(|_| {
+            Error::new(
+                ErrorCode::Malformed,
+                format!("Expected {}-byte value, got {} bytes", N, self.value.len()),
+            )
+            .with_field_type(self.field_type)
+        })
+    }
+
+    fn str(&self) -> Result<&'de str, Error> {
+        std::str::from_utf8(self.value).map_err(|e| {
+            Error::new(ErrorCode::Malformed, format!("Invalid UTF-8 value: {e}"))
+                .with_field_type(self.field_type)
+        })
+    }
+}
+
+macro_rules! deserialize_be {
+    ($($method:ident => $visit:ident($ty:ty);)*) => {
+        $(
+            fn $method<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+                visitor.$visit(<$ty>::from_be_bytes(self.fixed()?))
+            }
+        )*
+    };
+}
+
+impl<'de> de::Deserializer<'de> for FieldDeserializer<'de> {
+    type Error = Error;
+
+    deserialize_be! {
+        deserialize_i8 => visit_i8(i8);
+        deserialize_i16 => visit_i16(i16);
+        deserialize_i32 => visit_i32(i32);
+        deserialize_i64 => visit_i64(i64);
+        deserialize_u8 => visit_u8(u8);
+        deserialize_u16 => visit_u16(u16);
+        deserialize_u32 => visit_u32(u32);
+        deserialize_u64 => visit_u64(u64);
+        deserialize_f32 => visit_f32(f32);
+        deserialize_f64 => visit_f64(f64);
+    }
+
+    // The wire format is not self-describing; raw bytes are the best guess
+    fn deserialize_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.value)
+    }
+
+    fn deserialize_bool<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        match self.fixed::<1>()? {
+            [0] => visitor.visit_bool(false),
+            [1] => visitor.visit_bool(true),
+            [other] => Err(Error::new(
+                ErrorCode::Malformed,
+                format!("Invalid boolean value {other}"),
+            )
+            .with_field_type(self.field_type)),
+        }
+    }
+
+    fn deserialize_char<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        let mut chars = self.str()?.chars();
+        match (chars.next(), chars.next()) {
+            (Some(c), None) => visitor.visit_char(c),
+            _ => Err(
+                Error::new(ErrorCode::Malformed, "Expected a single character".into())
+                    .with_field_type(self.field_type),
+            ),
+        }
+    }
+
+    fn deserialize_str<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_str(self.str()?)
+    }
+
+    fn deserialize_string<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_bytes<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.value)
+    }
+
+    fn deserialize_byte_buf<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_borrowed_bytes(self.value)
+    }
+
+    fn deserialize_option<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_some(self)
+    }
+
+    fn deserialize_unit<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_unit_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+
+    fn deserialize_newtype_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        visitor.visit_newtype_struct(self)
+    }
+
+    fn deserialize_seq<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(unsupported("Nested sequence"))
+    }
+
+    fn deserialize_tuple<V: Visitor<'de>>(self, _: usize, _: V) -> Result<V::Value, Error> {
+        Err(unsupported("Tuple"))
+    }
+
+    fn deserialize_tuple_struct<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: usize,
+        _: V,
+    ) -> Result<V::Value, Error> {
+        Err(unsupported("Tuple struct"))
+    }
+
+    fn deserialize_map<V: Visitor<'de>>(self, _: V) -> Result<V::Value, Error> {
+        Err(unsupported("Map"))
+    }
+
+    fn deserialize_struct<V: Visitor<'de>>(
+        self,
+        name: &'static str,
+        fields: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let max_depth = self.limits.max_nesting_depth();
+        if self.depth >= max_depth {
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                format!("Maximum nesting depth of {max_depth} exceeded"),
+            )
+            .with_field_type(self.field_type));
+        }
+
+        let view = MessageRef::parse_with_limits(self.value, &self.limits)
+            .map_err(|e| e.with_field_type(self.field_type))?;
+        MessageDeserializer {
+            source: Source::Borrowed(view),
+            depth: self.depth + 1,
+            limits: self.limits,
+        }
+        .deserialize_struct(name, fields, visitor)
+    }
+
+    // Unit enum variants are stored as their u32 index
+    fn deserialize_enum<V: Visitor<'de>>(
+        self,
+        _: &'static str,
+        _: &'static [&'static str],
+        visitor: V,
+    ) -> Result<V::Value, Error> {
+        let index: U32Deserializer<Error> = u32::from_be_bytes(self.fixed()?).into_deserializer();
+        visitor.visit_enum(index)
+    }
+
+    fn deserialize_identifier<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        self.deserialize_str(visitor)
+    }
+
+    fn deserialize_ignored_any<V: Visitor<'de>>(self, visitor: V) -> Result<V::Value, Error> {
+        visitor.visit_unit()
+    }
+}
@@ -0,0 +1,140 @@
+#[cfg(feature = "auth")]
+use crate::auth::Keyring;
+use crate::error::Error;
+use crate::message::Message;
+use crate::wire::WireConfig;
+use bytes::{Bytes, BytesMut};
+
+/// Splits one complete frame off the front of `buf`.
+///
+/// Returns `Ok(None)` while the buffer does not yet hold a whole frame.
+pub(crate) fn split_frame(buf: &mut BytesMut, config: &WireConfig) -> Result<Option<Bytes>, Error> {
+    let declared_length = match config.frame_len(buf)? {
+        Some(len) => len,
+        None => return Ok(None),
+    };
+
+    if buf.len() < declared_length {
+        crate::rapid_trace!(
+            "Frame incomplete: {} of {} bytes buffered",
+            buf.len(),
+            declared_length
+        );
+        buf.reserve(declared_length - buf.len());
+        return Ok(None);
+    }
+
+    Ok(Some(buf.split_to(declared_length).freeze()))
+}
+
+/// Stateful decoder that reassembles `Message`s from arbitrarily sized chunks,
+/// e.g. the results of consecutive socket reads.
+#[derive(Debug, Default)]
+pub struct FrameDecoder {
+    buffer: BytesMut,
+    config: WireConfig,
+    #[cfg(feature = "auth")]
+    keyring: Option<Keyring>,
+}
+
+impl FrameDecoder {
+    pub fn new() -> FrameDecoder {
+        FrameDecoder::default()
+    }
+
+    pub fn with_capacity(capacity: usize) -> FrameDecoder {
+        FrameDecoder {
+            buffer: BytesMut::with_capacity(capacity),
+            ..FrameDecoder::default()
+        }
+    }
+
+    /// Decodes frames laid out as described by `config`.
+    pub fn with_config(mut self, config: WireConfig) -> FrameDecoder {
+        self.config = config;
+        self
+    }
+
+    pub fn config(&self) -> &WireConfig {
+        &self.config
+    }
+
+    /// Decodes authenticated frames, rejecting any whose tag does not
+    /// verify with `keyring`.
+    #[cfg(feature = "auth")]
+    pub fn with_keyring(mut self, keyring: Keyring) -> FrameDecoder {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    /// Appends a chunk of received bytes to the internal buffer.
+    pub fn extend(&mut self, chunk: &[u8]) {
+        crate::rapid_trace!("Buffering {} bytes in frame decoder", chunk.len());
+        self.buffer.extend_from_slice(chunk);
+    }
+
+    /// Gives direct access to the internal buffer so readers can fill it in place.
+    pub fn buffer_mut(&mut self) -> &mut BytesMut {
+        &mut self.buffer
+    }
+
+    /// Number of bytes currently buffered but not yet decoded.
+    pub fn buffered(&self) -> usize {
+        self.buffer.len()
+    }
+
+    /// Minimum number of additional bytes required before the next frame can be
+    /// decoded. Returns 0 when a complete frame is already buffered.
+    pub fn bytes_needed(&self) -> usize {
+        let header_len = self.config.header_len();
+        match self.config.frame_len(&self.buffer) {
+            Ok(Some(len)) => len.saturating_sub(self.buffer.len()),
+            // A corrupt length prefix is reported by the next decode
+            Err(_) => 0,
+            Ok(None) => header_len.saturating_sub(self.buffer.len()),
+        }
+    }
+
+    /// Decodes the next complete message from the buffer, if there is one.
+    ///
+    /// If the length prefix is invalid, the stream cannot be resynchronised: the
+    /// offending bytes stay buffered and the caller should drop the connection
+    /// or call `clear`. Any other error consumes only the frame that failed to
+    /// parse, and the next call decodes the frame after it.
+    pub fn decode(&mut self) -> Result<Option<Message>, Error> {
+        match split_frame(&mut self.buffer, &self.config)? {
+            Some(frame) => self.parse(frame).map(Some),
+            None => Ok(None),
+        }
+    }
+
+    fn parse(&self, frame: Bytes) -> Result<Message, Error> {
+        #[cfg(feature = "auth")]
+        if let Some(keyring) = &self.keyring {
+            return keyring.parse(frame, &self.config);
+        }
+        self.config.parse(frame)
+    }
+
+    /// Buffers `chunk` and decodes every message that is now complete.
+    pub fn feed(&mut self, chunk: &[u8]) -> Result<Vec<Message>, Error> {
+        self.extend(chunk);
+
+        let mut messages = Vec::new();
+        while let Some(msg) = self.decode()? {
+            messages.push(msg);
+        }
+
+        crate::rapid_debug!(
+            "Frame decoder produced {} messages, {} bytes left buffered",
+            messages.len(),
+            self.buffer.len()
+        );
+        Ok(messages)
+    }
+
+    /// Discards all buffered bytes.
+    pub fn clear(&mut self) {
+        self.buffer.clear();
+    }
+}
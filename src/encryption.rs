@@ -0,0 +1,342 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::{Field, FieldType};
+use crate::message::{EventType, Message};
+use aes_gcm::Aes256Gcm;
+use bytes::{BufMut, Bytes, BytesMut};
+use chacha20poly1305::ChaCha20Poly1305;
+use chacha20poly1305::aead::rand_core::RngCore;
+use chacha20poly1305::aead::{Aead, KeyInit, Nonce, OsRng, Payload};
+use std::fmt;
+
+/// Length of an encryption key; both ciphers take 256-bit keys.
+pub const ENCRYPTION_KEY_LEN: usize = 32;
+
+const NONCE_LEN: usize = 12;
+const TAG_LEN: usize = 16;
+
+/// AEAD algorithm used to encrypt field values.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub enum Cipher {
+    ChaCha20Poly1305,
+    Aes256Gcm,
+}
+
+#[derive(Clone, PartialEq, Eq)]
+enum Scope {
+    // Sorted and deduplicated
+    Fields(Vec<FieldType>),
+    Section(FieldType),
+}
+
+/// Encrypts and decrypts field values with an AEAD cipher.
+///
+/// Either the values of selected field types are encrypted one by one, or
+/// the whole fields section is encrypted into a single field. The event
+/// type stays readable for routing, and the correlation id is kept. Each
+/// encrypted value is `[nonce (12)][ciphertext][tag (16)]` with a random
+/// nonce. It is bound to its event and field type, to the nonces of the
+/// other encrypted values and to the unencrypted fields, so it cannot be
+/// moved to another field or message, and the readable fields cannot be
+/// changed, unnoticed. A whole message can still be replayed.
+///
+/// Both sides must use the same cipher, key and selection.
+#[derive(Clone)]
+pub struct Encryption {
+    cipher: Cipher,
+    key: [u8; ENCRYPTION_KEY_LEN],
+    scope: Scope,
+}
+
+impl Encryption {
+    /// Encrypts the values of the given field types and leaves others readable.
+    pub fn fields(
+        cipher: Cipher,
+        key: [u8; ENCRYPTION_KEY_LEN],
+        field_types: &[FieldType],
+    ) -> Encryption {
+        let mut field_types = field_types.to_vec();
+        field_types.sort_unstable();
+        field_types.dedup();
+        Encryption {
+            cipher,
+            key,
+            scope: Scope::Fields(field_types),
+        }
+    }
+
+    /// Encrypts the whole fields section into one field of type `field_type`.
+    pub fn section(
+        cipher: Cipher,
+        key: [u8; ENCRYPTION_KEY_LEN],
+        field_type: FieldType,
+    ) -> Encryption {
+        Encryption {
+            cipher,
+            key,
+            scope: Scope::Section(field_type),
+        }
+    }
+
+    pub fn cipher(&self) -> Cipher {
+        self.cipher
+    }
+
+    /// Returns a copy of `msg` with the selected fields encrypted.
+    pub fn encrypt(&self, msg: &Message) -> Result<Message, Error> {
+        let mut encrypted = Message::new(msg.event_type);
+        encrypted.set_correlation_id(msg.correlation_id());
+        match &self.scope {
+            Scope::Fields(field_types) => {
+                let fields: Vec<&Field> = msg.fields().collect();
+                let nonces: Vec<_> = fields
+                    .iter()
+                    .map(|field| selected(field_types, field).then(random_nonce))
+                    .collect();
+                let binding = binding(&fields, &nonces);
+                for (field, nonce) in fields.iter().zip(&nonces) {
+                    let field_type = *field.field_type();
+                    let value = match nonce {
+                        Some(nonce) => {
+                            let aad = aad(msg.event_type, field_type, &binding);
+                            self.seal(field_type, nonce, &aad, field.value())?
+                        }
+                        None => field.bytes().clone(),
+                    };
+                    encrypted.add_repeated_field(field_type, value);
+                }
+            }
+            Scope::Section(section) => {
+                let mut plaintext = BytesMut::new();
+                for field in msg.fields() {
+                    plaintext.put(field.encode()?);
+                }
+                let aad = aad(msg.event_type, *section, &[]);
+                encrypted.add_field(
+                    *section,
+                    self.seal(*section, &random_nonce(), &aad, &plaintext)?,
+                );
+            }
+        }
+        crate::rapid_debug!(
+            "Encrypted message with event type {} using {:?}",
+            msg.event_type,
+            self.cipher
+        );
+        Ok(encrypted)
+    }
+
+    /// Returns a copy of `msg` with the selected fields decrypted. The copy
+    /// keeps the parse limits of `msg`.
+    ///
+    /// Fails with `AuthenticationFailed` if a value was not encrypted with
+    /// this key for this event and field type, or it or the rest of the
+    /// message was altered.
+    pub fn decrypt(&self, msg: &Message) -> Result<Message, Error> {
+        let mut decrypted = match &self.scope {
+            Scope::Fields(field_types) => {
+                let fields: Vec<&Field> = msg.fields().collect();
+                let nonces = fields
+                    .iter()
+                    .map(|field| {
+                        selected(field_types, field)
+                            .then(|| read_nonce(field))
+                            .transpose()
+                    })
+                    .collect::<Result<Vec<_>, Error>>()?;
+                let binding = binding(&fields, &nonces);
+
+                let mut decrypted = Message::new(msg.event_type);
+                for (field, nonce) in fields.iter().zip(&nonces) {
+                    let field_type = *field.field_type();
+                    let value = match nonce {
+                        Some(_) => {
+                            let aad = aad(msg.event_type, field_type, &binding);
+                            self.open(field_type, &aad, field.value())?
+                        }
+                        None => field.bytes().clone(),
+                    };
+                    decrypted.add_repeated_field(field_type, value);
+                }
+                decrypted
+            }
+            Scope::Section(section) => {
+                let mut sections = msg.get_fields(section);
+                let (Some(sealed), None) = (sections.next(), sections.next()) else {
+                    return Err(Error::new(
+                        ErrorCode::Malformed,
+                        format!("Expected exactly one encrypted section in field type {section}"),
+                    )
+                    .with_field_type(*section));
+                };
+                read_nonce(sealed)?;
+                let aad = aad(msg.event_type, *section, &[]);
+                let plaintext = self.open(*section, &aad, sealed.value())?;
+
+                let len = u32::try_from(5 + plaintext.len()).map_err(|_| {
+                    Error::new(
+                        ErrorCode::ValueTooLarge,
+                        format!(
+                            "Decrypted section of {} bytes does not fit in a frame",
+                            plaintext.len()
+                        ),
+                    )
+                    .with_field_type(*section)
+                })?;
+                let mut frame = BytesMut::with_capacity(len as usize);
+                frame.put_u32(len);
+                frame.put_u8(msg.event_type);
+                frame.put(plaintext);
+                Message::parse_with_limits(frame.freeze(), msg.limits())?
+            }
+        };
+        decrypted.set_limits(*msg.limits());
+        decrypted.set_correlation_id(msg.correlation_id());
+        Ok(decrypted)
+    }
+
+    fn seal(
+        &self,
+        field_type: FieldType,
+        nonce: &[u8; NONCE_LEN],
+        aad: &[u8],
+        plaintext: &[u8],
+    ) -> Result<Bytes, Error> {
+        let sealed = match self.cipher {
+            Cipher::ChaCha20Poly1305 => {
+                seal_with::<ChaCha20Poly1305>(&self.key, nonce, aad, plaintext)
+            }
+            Cipher::Aes256Gcm => seal_with::<Aes256Gcm>(&self.key, nonce, aad, plaintext),
+        };
+        sealed.map(Bytes::from).map_err(|_| {
+            Error::new(
+                ErrorCode::InternalServerError,
+                format!("Encryption with {:?} failed", self.cipher),
+            )
+            .with_field_type(field_type)
+        })
+    }
+
+    // `sealed` has been checked by read_nonce
+    fn open(&self, field_type: FieldType, aad: &[u8], sealed: &[u8]) -> Result<Bytes, Error> {
+        let opened = match self.cipher {
+            Cipher::ChaCha20Poly1305 => open_with::<ChaCha20Poly1305>(&self.key, aad, sealed),
+            Cipher::Aes256Gcm => open_with::<Aes256Gcm>(&self.key, aad, sealed),
+        };
+        opened.map(Bytes::from).map_err(|_| {
+            crate::rapid_warn!(
+                "Decryption of field type {} failed: Tag mismatch",
+                field_type
+            );
+            Error::new(
+                ErrorCode::AuthenticationFailed,
+                "Encrypted value does not verify".into(),
+            )
+            .with_field_type(field_type)
+        })
+    }
+}
+
+fn selected(field_types: &[FieldType], field: &Field) -> bool {
+    field_types.binary_search(field.field_type()).is_ok()
+}
+
+fn random_nonce() -> [u8; NONCE_LEN] {
+    let mut nonce = [0u8; NONCE_LEN];
+    OsRng.fill_bytes(&mut nonce);
+    nonce
+}
+
+fn read_nonce(field: &Field) -> Result<[u8; NONCE_LEN], Error> {
+    let sealed = field.value();
+    if sealed.len() < NONCE_LEN + TAG_LEN {
+        return Err(Error::new(
+            ErrorCode::Malformed,
+            format!(
+                "Encrypted value of {} bytes is shorter than nonce and tag",
+                sealed.len()
+            ),
+        )
+        .with_field_type(*field.field_type()));
+    }
+    Ok(sealed[..NONCE_LEN].try_into().unwrap())
+}
+
+// What every encrypted value of a message is bound to besides its own event
+// and field type: the nonces of all encrypted values and the unencrypted
+// fields, in field type order. Field types take two bytes.
+fn binding(fields: &[&Field], nonces: &[Option<[u8; NONCE_LEN]>]) -> Vec<u8> {
+    let mut order: Vec<usize> = (0..fields.len()).collect();
+    order.sort_by_key(|&i| *fields[i].field_type());
+
+    let mut binding = Vec::new();
+    for i in order {
+        match &nonces[i] {
+            Some(nonce) => {
+                binding.extend_from_slice(&fields[i].field_type().to_be_bytes());
+                binding.extend_from_slice(nonce);
+            }
+            None => {
+                let value = fields[i].value();
+                binding.extend_from_slice(&fields[i].field_type().to_be_bytes());
+                binding.extend_from_slice(&(value.len() as u32).to_be_bytes());
+                binding.extend_from_slice(value);
+            }
+        }
+    }
+    binding
+}
+
+fn aad(event_type: EventType, field_type: FieldType, binding: &[u8]) -> Vec<u8> {
+    let mut aad = Vec::with_capacity(3 + binding.len());
+    aad.push(event_type);
+    aad.extend_from_slice(&field_type.to_be_bytes());
+    aad.extend_from_slice(binding);
+    aad
+}
+
+fn seal_with<C: KeyInit + Aead>(
+    key: &[u8; ENCRYPTION_KEY_LEN],
+    nonce: &[u8; NONCE_LEN],
+    aad: &[u8],
+    plaintext: &[u8],
+) -> Result<Vec<u8>, chacha20poly1305::aead::Error> {
+    let cipher = C::new_from_slice(key).expect("both ciphers take 256-bit keys");
+    let mut sealed = nonce.to_vec();
+    sealed.extend(cipher.encrypt(
+        Nonce::<C>::from_slice(nonce),
+        Payload {
+            msg: plaintext,
+            aad,
+        },
+    )?);
+    Ok(sealed)
+}
+
+fn open_with<C: KeyInit + Aead>(
+    key: &[u8; ENCRYPTION_KEY_LEN],
+    aad: &[u8],
+    sealed: &[u8],
+) -> Result<Vec<u8>, chacha20poly1305::aead::Error> {
+    let cipher = C::new_from_slice(key).expect("both ciphers take 256-bit keys");
+    let (nonce, ciphertext) = sealed.split_at(NONCE_LEN);
+    cipher.decrypt(
+        Nonce::<C>::from_slice(nonce),
+        Payload {
+            msg: ciphertext,
+            aad,
+        },
+    )
+}
+
+// The key is secret
+impl fmt::Debug for Encryption {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let mut debug = f.debug_struct("Encryption");
+        debug.field("cipher", &self.cipher);
+        match &self.scope {
+            Scope::Fields(field_types) => debug.field("fields", field_types),
+            Scope::Section(field_type) => debug.field("section", field_type),
+        };
+        debug.finish_non_exhaustive()
+    }
+}
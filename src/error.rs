@@ -1,10 +1,30 @@
-#[derive(Debug)]
+use crate::field::FieldType;
+use crate::message::{EventType, Message};
+use bytes::Bytes;
+use std::fmt;
+
+/// Reserved event type for error-response messages.
+pub const EVT_ERROR: EventType = 0xFF;
+
+/// Error code as a big-endian u16 (required).
+pub const FIELD_ERROR_CODE: FieldType = 0x01;
+/// UTF-8 error message (required, may be empty).
+pub const FIELD_ERROR_MESSAGE: FieldType = 0x02;
+/// Byte offset where decoding failed as a big-endian u32 (optional).
+pub const FIELD_ERROR_OFFSET: FieldType = 0x03;
+/// Type of the field that failed to decode (optional, 1 byte).
+pub const FIELD_ERROR_FIELD_TYPE: FieldType = 0x04;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+#[repr(u16)]
 pub enum ErrorCode {
     // Protocol errors (0x0001-0x0100)
     InvalidEventType = 0x01,
     Malformed = 0x02,
     IncompleteMessage = 0x03,
     UnsupportedVersion = 0x04,
+    ChecksumMismatch = 0x05,
+    AuthenticationFailed = 0x06,
 
     // Application errors (0x0101-0x0200)
     KeyNotFound = 0x11,
@@ -30,10 +50,66 @@ pub enum ErrorCode {
     WriteFailed = 0x52,
 }
 
-#[derive(Debug)]
+impl ErrorCode {
+    const ALL: [ErrorCode; 20] = [
+        ErrorCode::InvalidEventType,
+        ErrorCode::Malformed,
+        ErrorCode::IncompleteMessage,
+        ErrorCode::UnsupportedVersion,
+        ErrorCode::ChecksumMismatch,
+        ErrorCode::AuthenticationFailed,
+        ErrorCode::KeyNotFound,
+        ErrorCode::TtlExpired,
+        ErrorCode::ValueTooLarge,
+        ErrorCode::DiskWriteFailed,
+        ErrorCode::ReadonlyMode,
+        ErrorCode::MasterUnavailable,
+        ErrorCode::SyncDenied,
+        ErrorCode::InternalServerError,
+        ErrorCode::ConfigInvalid,
+        ErrorCode::ConnectionFailed,
+        ErrorCode::SendFailed,
+        ErrorCode::NotConnected,
+        ErrorCode::ReadFailed,
+        ErrorCode::WriteFailed,
+    ];
+}
+
+impl From<ErrorCode> for u16 {
+    fn from(code: ErrorCode) -> u16 {
+        code as u16
+    }
+}
+
+impl TryFrom<u16> for ErrorCode {
+    type Error = Error;
+
+    fn try_from(value: u16) -> Result<ErrorCode, Error> {
+        ErrorCode::ALL
+            .iter()
+            .copied()
+            .find(|code| *code as u16 == value)
+            .ok_or_else(|| {
+                Error::new(
+                    ErrorCode::Malformed,
+                    format!("Unknown error code {value:#06x}"),
+                )
+            })
+    }
+}
+
+impl fmt::Display for ErrorCode {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{:?} ({:#06x})", self, *self as u16)
+    }
+}
+
+#[derive(Debug, Clone)]
 pub struct Error {
     code: ErrorCode,
-    message: Vec<u8>,
+    message: String,
+    offset: Option<usize>,
+    field_type: Option<FieldType>,
 }
 
 impl Error {
@@ -41,7 +117,149 @@ impl Error {
         crate::rapid_error!("Error created: {:?} - {}", code, message);
         Error {
             code,
-            message: message.into_bytes(),
+            message,
+            offset: None,
+            field_type: None,
+        }
+    }
+
+    /// Records the byte offset in the frame at which decoding failed.
+    pub fn at_offset(mut self, offset: usize) -> Error {
+        self.offset = Some(offset);
+        self
+    }
+
+    /// Records the type of the field that failed to decode.
+    pub fn with_field_type(mut self, field_type: FieldType) -> Error {
+        self.field_type = Some(field_type);
+        self
+    }
+
+    pub fn code(&self) -> ErrorCode {
+        self.code
+    }
+
+    pub fn message(&self) -> &str {
+        &self.message
+    }
+
+    pub fn offset(&self) -> Option<usize> {
+        self.offset
+    }
+
+    pub fn field_type(&self) -> Option<FieldType> {
+        self.field_type
+    }
+}
+
+impl Error {
+    /// Builds an `EVT_ERROR` response message carrying this error. An offset
+    /// that does not fit in 32 bits is left out.
+    pub fn to_message(&self) -> Message {
+        let mut msg = Message::new(EVT_ERROR)
+            .with_field(
+                FIELD_ERROR_CODE,
+                Bytes::copy_from_slice(&u16::from(self.code).to_be_bytes()),
+            )
+            .with_field(
+                FIELD_ERROR_MESSAGE,
+                Bytes::copy_from_slice(self.message.as_bytes()),
+            );
+
+        if let Some(offset) = self.offset.and_then(|offset| u32::try_from(offset).ok()) {
+            msg.add_field(
+                FIELD_ERROR_OFFSET,
+                Bytes::copy_from_slice(&offset.to_be_bytes()),
+            );
+        }
+        if let Some(field_type) = self.field_type {
+            // One byte unless the type needs two
+            let bytes = field_type.to_be_bytes();
+            let start = if field_type > u8::MAX as FieldType {
+                0
+            } else {
+                1
+            };
+            msg.add_field(
+                FIELD_ERROR_FIELD_TYPE,
+                Bytes::copy_from_slice(&bytes[start..]),
+            );
+        }
+        msg
+    }
+
+    /// Reads an error back from an `EVT_ERROR` response message.
+    pub fn from_message(msg: &Message) -> Result<Error, Error> {
+        if msg.event_type != EVT_ERROR {
+            return Err(Error::new(
+                ErrorCode::InvalidEventType,
+                format!("Expected error event type, got {}", msg.event_type),
+            ));
+        }
+
+        let code = match msg.get_field(&FIELD_ERROR_CODE).map(|f| f.value()) {
+            Some(&[hi, lo]) => ErrorCode::try_from(u16::from_be_bytes([hi, lo]))?,
+            _ => return Err(malformed_response(FIELD_ERROR_CODE)),
+        };
+
+        let message = match msg.get_field(&FIELD_ERROR_MESSAGE) {
+            Some(field) => std::str::from_utf8(field.value())
+                .map_err(|_| malformed_response(FIELD_ERROR_MESSAGE))?
+                .to_string(),
+            None => return Err(malformed_response(FIELD_ERROR_MESSAGE)),
+        };
+
+        let offset = match msg.get_field(&FIELD_ERROR_OFFSET).map(|f| f.value()) {
+            Some(&[a, b, c, d]) => Some(u32::from_be_bytes([a, b, c, d]) as usize),
+            Some(_) => return Err(malformed_response(FIELD_ERROR_OFFSET)),
+            None => None,
+        };
+
+        let field_type = match msg.get_field(&FIELD_ERROR_FIELD_TYPE).map(|f| f.value()) {
+            Some(&[field_type]) => Some(FieldType::from(field_type)),
+            Some(&[hi, lo]) => Some(FieldType::from_be_bytes([hi, lo])),
+            Some(_) => return Err(malformed_response(FIELD_ERROR_FIELD_TYPE)),
+            None => None,
+        };
+
+        Ok(Error {
+            code,
+            message,
+            offset,
+            field_type,
+        })
+    }
+}
+
+fn malformed_response(field_type: FieldType) -> Error {
+    Error::new(
+        ErrorCode::Malformed,
+        "Missing or invalid field in error response".into(),
+    )
+    .with_field_type(field_type)
+}
+
+impl fmt::Display for Error {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}: {}", self.code, self.message)?;
+        if let Some(offset) = self.offset {
+            write!(f, " at offset {offset}")?;
+        }
+        if let Some(field_type) = self.field_type {
+            write!(f, " in field type {field_type}")?;
         }
+        Ok(())
+    }
+}
+
+impl std::error::Error for Error {}
+
+impl From<std::io::Error> for Error {
+    fn from(err: std::io::Error) -> Error {
+        let code = match err.kind() {
+            std::io::ErrorKind::UnexpectedEof => ErrorCode::IncompleteMessage,
+            _ => ErrorCode::ReadFailed,
+        };
+        Error::new(code, err.to_string())
     }
 }
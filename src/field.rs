@@ -1,9 +1,24 @@
 use crate::error::{Error, ErrorCode};
-use bytes::{BufMut, Bytes};
+use crate::format::WireFormat;
+use bytes::{BufMut, Bytes, BytesMut};
 
-pub type FieldType = u8;
+/// Field types are two bytes wide for formats with two-byte field headers; the
+/// default format only holds types up to 255.
+pub type FieldType = u16;
 
-#[derive(Debug, Clone)]
+/// The field type as written in a one-byte field header.
+pub(crate) fn narrow_type(field_type: FieldType) -> Result<u8, Error> {
+    u8::try_from(field_type).map_err(|_| {
+        Error::new(
+            ErrorCode::ValueTooLarge,
+            format!("Field type {field_type} does not fit a one-byte field header"),
+        )
+        .with_field_type(field_type)
+    })
+}
+
+/// Fields compare and hash by type and value.
+#[derive(Debug, Clone, PartialEq, Eq, Hash)]
 pub struct Field {
     field_type: FieldType,
     value: Bytes,
@@ -27,6 +42,11 @@ impl Field {
         &self.value
     }
 
+    /// The value as shared `Bytes`, for zero-copy reuse.
+    pub fn bytes(&self) -> &Bytes {
+        &self.value
+    }
+
     pub fn update_value(&mut self, value: Bytes) {
         crate::rapid_trace!(
             "Updating field type {} value from {} bytes to {} bytes",
@@ -37,26 +57,24 @@ impl Field {
         self.value = value;
     }
 
+    #[allow(clippy::len_without_is_empty)]
     pub fn len(&self) -> usize {
         self.value.len() + 1 + 4 // 1 byte for a field type, 4 bytes for length
     }
 
     pub fn encode(&self) -> Result<Bytes, Error> {
+        self.encode_with(&WireFormat::default())
+    }
+
+    /// Encodes the field with the header layout of `format`.
+    pub fn encode_with(&self, format: &WireFormat) -> Result<Bytes, Error> {
         crate::rapid_trace!(
             "Encoding field type {} with {} bytes",
             self.field_type,
             self.value.len()
         );
-        let mut buffer = bytes::BytesMut::with_capacity(1 + 4 + self.value.len());
-
-        // write a field type
-        buffer.put_u8(self.field_type);
-
-        // write length (big endian)
-        buffer.put_u32(self.value.len() as u32);
-
-        // write value
-        buffer.put_slice(&self.value);
+        let mut buffer = BytesMut::with_capacity(format.field_len(self.value.len()));
+        self.write_with(format, &mut buffer)?;
 
         let result = buffer.freeze();
         crate::rapid_trace!(
@@ -65,4 +83,79 @@ impl Field {
         );
         Ok(result)
     }
+
+    /// Appends header and value to `buffer`.
+    pub(crate) fn write_with(
+        &self,
+        format: &WireFormat,
+        buffer: &mut BytesMut,
+    ) -> Result<(), Error> {
+        format.put_field_header(buffer, self.field_type, self.value.len())?;
+        buffer.put_slice(&self.value);
+        Ok(())
+    }
+}
+
+// Typed accessors for fixed-width big-endian integers
+macro_rules! int_accessors {
+    ($($get:ident, $set:ident, $ty:ty;)*) => {
+        $(
+            pub fn $get(&self) -> Result<$ty, Error> {
+                Ok(<$ty>::from_be_bytes(self.fixed_width()?))
+            }
+
+            pub fn $set(&mut self, value: $ty) {
+                self.update_value(Bytes::copy_from_slice(&value.to_be_bytes()));
+            }
+        )*
+    };
+}
+
+impl Field {
+    int_accessors! {
+        get_u8, set_u8, u8;
+        get_u16, set_u16, u16;
+        get_u32, set_u32, u32;
+        get_u64, set_u64, u64;
+        get_i64, set_i64, i64;
+        get_f64, set_f64, f64;
+    }
+
+    /// Reads a boolean encoded as a single 0 or 1 byte.
+    pub fn get_bool(&self) -> Result<bool, Error> {
+        match self.fixed_width::<1>()? {
+            [0] => Ok(false),
+            [1] => Ok(true),
+            [other] => Err(Error::new(
+                ErrorCode::Malformed,
+                format!("Invalid boolean value {other}"),
+            )
+            .with_field_type(self.field_type)),
+        }
+    }
+
+    pub fn set_bool(&mut self, value: bool) {
+        self.update_value(Bytes::copy_from_slice(&[value as u8]));
+    }
+
+    pub fn get_str(&self) -> Result<&str, Error> {
+        std::str::from_utf8(&self.value).map_err(|e| {
+            Error::new(ErrorCode::Malformed, format!("Invalid UTF-8 value: {e}"))
+                .with_field_type(self.field_type)
+        })
+    }
+
+    pub fn set_str(&mut self, value: &str) {
+        self.update_value(Bytes::copy_from_slice(value.as_bytes()));
+    }
+
+    fn fixed_width<const N: usize>(&self) -> Result<[u8; N], Error> {
+        self.value[..].try_into().map_err(|_| {
+            Error::new(
+                ErrorCode::Malformed,
+                format!("Expected {}-byte value, got {} bytes", N, self.value.len()),
+            )
+            .with_field_type(self.field_type)
+        })
+    }
 }
@@ -0,0 +1,330 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::{FieldType, narrow_type};
+use bytes::{BufMut, BytesMut};
+
+/// Byte order of the integers in frame and field headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Endianness {
+    #[default]
+    Big,
+    Little,
+}
+
+/// Width of a length prefix.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LengthWidth {
+    U8,
+    U16,
+    U32,
+    /// LEB128: 7 bits per byte, low bits first, one byte below 128
+    Varint,
+}
+
+// A varint length covers the u32 range
+const MAX_VARINT_LEN: usize = 5;
+
+impl LengthWidth {
+    // Bytes needed to encode `value`
+    fn len(self, value: usize) -> usize {
+        match self {
+            LengthWidth::U8 => 1,
+            LengthWidth::U16 => 2,
+            LengthWidth::U32 => 4,
+            LengthWidth::Varint => varint_len(value as u64),
+        }
+    }
+
+    fn max(self) -> usize {
+        match self {
+            LengthWidth::U8 => u8::MAX as usize,
+            LengthWidth::U16 => u16::MAX as usize,
+            LengthWidth::U32 | LengthWidth::Varint => u32::MAX as usize,
+        }
+    }
+}
+
+fn varint_len(value: u64) -> usize {
+    (64 - (value | 1).leading_zeros() as usize).div_ceil(7)
+}
+
+/// Decodes a LEB128 varint; `Ok(None)` if `buf` ends inside it.
+fn read_varint(buf: &[u8]) -> Result<Option<(u64, usize)>, Error> {
+    let mut value = 0u64;
+    for (i, byte) in buf.iter().enumerate().take(MAX_VARINT_LEN) {
+        value |= ((byte & 0x7F) as u64) << (7 * i);
+        if byte & 0x80 == 0 {
+            if value > u32::MAX as u64 {
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    format!("Varint length {value} exceeds the u32 range"),
+                ));
+            }
+            return Ok(Some((value, i + 1)));
+        }
+    }
+    if buf.len() < MAX_VARINT_LEN {
+        return Ok(None);
+    }
+    Err(Error::new(
+        ErrorCode::Malformed,
+        format!("Varint length is longer than {MAX_VARINT_LEN} bytes"),
+    ))
+}
+
+fn put_varint(buf: &mut BytesMut, mut value: u64) {
+    while value >= 0x80 {
+        buf.put_u8(value as u8 | 0x80);
+        value >>= 7;
+    }
+    buf.put_u8(value as u8);
+}
+
+/// Width of the field type in field headers.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldTypeWidth {
+    #[default]
+    U8,
+    /// Two bytes, for the whole `FieldType` range. With one byte, fields
+    /// of types above 255 fail to encode with `ValueTooLarge`.
+    U16,
+}
+
+/// Layout of the length prefix and field headers.
+///
+/// The default is this crate's own format: a 4-byte message length that
+/// includes itself, 1-byte field types and 4-byte field lengths, all
+/// big-endian. Other layouts exist for peers that speak a variant of the
+/// protocol. Field values are never reinterpreted.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct WireFormat {
+    message_length: LengthWidth,
+    length_inclusive: bool,
+    field_type: FieldTypeWidth,
+    field_length: LengthWidth,
+    endianness: Endianness,
+}
+
+impl Default for WireFormat {
+    fn default() -> Self {
+        WireFormat {
+            message_length: LengthWidth::U32,
+            length_inclusive: true,
+            field_type: FieldTypeWidth::U8,
+            field_length: LengthWidth::U32,
+            endianness: Endianness::Big,
+        }
+    }
+}
+
+impl WireFormat {
+    pub fn new() -> WireFormat {
+        WireFormat::default()
+    }
+
+    /// Varint message and field lengths, for links carrying mostly small
+    /// values: a field under 128 bytes costs two header bytes instead of five.
+    pub fn compact() -> WireFormat {
+        WireFormat {
+            message_length: LengthWidth::Varint,
+            length_inclusive: false,
+            field_length: LengthWidth::Varint,
+            ..WireFormat::default()
+        }
+    }
+
+    pub fn message_length(&self) -> LengthWidth {
+        self.message_length
+    }
+
+    pub fn with_message_length(mut self, width: LengthWidth) -> WireFormat {
+        self.message_length = width;
+        self
+    }
+
+    /// Whether the message length counts its own prefix.
+    pub fn length_inclusive(&self) -> bool {
+        self.length_inclusive
+    }
+
+    pub fn with_length_inclusive(mut self, inclusive: bool) -> WireFormat {
+        self.length_inclusive = inclusive;
+        self
+    }
+
+    pub fn field_type(&self) -> FieldTypeWidth {
+        self.field_type
+    }
+
+    pub fn with_field_type(mut self, width: FieldTypeWidth) -> WireFormat {
+        self.field_type = width;
+        self
+    }
+
+    pub fn field_length(&self) -> LengthWidth {
+        self.field_length
+    }
+
+    pub fn with_field_length(mut self, width: LengthWidth) -> WireFormat {
+        self.field_length = width;
+        self
+    }
+
+    pub fn endianness(&self) -> Endianness {
+        self.endianness
+    }
+
+    pub fn with_endianness(mut self, endianness: Endianness) -> WireFormat {
+        self.endianness = endianness;
+        self
+    }
+
+    /// Bytes taken by the shortest possible message length prefix.
+    pub(crate) fn min_prefix_len(&self) -> usize {
+        self.message_length.len(0)
+    }
+
+    pub(crate) fn has_varint_prefix(&self) -> bool {
+        self.message_length == LengthWidth::Varint
+    }
+
+    /// Decodes the message length prefix at the start of `buf` into the total
+    /// frame length and the size of the prefix. `Ok(None)` if it is incomplete.
+    pub(crate) fn read_prefix(&self, buf: &[u8]) -> Result<Option<(usize, usize)>, Error> {
+        let Some((declared, prefix_len)) = self.read_length(self.message_length, buf)? else {
+            return Ok(None);
+        };
+        let frame_len = if self.length_inclusive {
+            declared
+        } else {
+            declared + prefix_len
+        };
+        Ok(Some((frame_len, prefix_len)))
+    }
+
+    /// Size of a frame whose prefix is followed by `body_len` bytes.
+    pub(crate) fn frame_len(&self, body_len: usize) -> usize {
+        let mut prefix_len = self.message_length.len(body_len);
+        if self.length_inclusive {
+            // Counting the prefix itself may push a varint into another byte
+            while self.message_length.len(body_len + prefix_len) != prefix_len {
+                prefix_len += 1;
+            }
+        }
+        prefix_len + body_len
+    }
+
+    /// Writes the prefix of a frame whose prefix is followed by `body_len` bytes.
+    pub(crate) fn put_prefix(&self, buf: &mut BytesMut, body_len: usize) -> Result<(), Error> {
+        let frame_len = self.frame_len(body_len);
+        let declared = if self.length_inclusive {
+            frame_len
+        } else {
+            body_len
+        };
+        if declared > self.message_length.max() {
+            return Err(Error::new(
+                ErrorCode::ValueTooLarge,
+                format!("Message of {frame_len} bytes does not fit the length prefix"),
+            ));
+        }
+        self.put_length(buf, self.message_length, declared);
+        Ok(())
+    }
+
+    fn field_type_len(&self) -> usize {
+        match self.field_type {
+            FieldTypeWidth::U8 => 1,
+            FieldTypeWidth::U16 => 2,
+        }
+    }
+
+    /// Bytes taken by a field holding `value_len` bytes, header included.
+    pub(crate) fn field_len(&self, value_len: usize) -> usize {
+        self.field_type_len() + self.field_length.len(value_len) + value_len
+    }
+
+    /// Decodes a field header at the start of `buf` into field type, value
+    /// length and header size. `Ok(None)` if the header is incomplete.
+    pub(crate) fn read_field_header(
+        &self,
+        buf: &[u8],
+    ) -> Result<Option<(FieldType, usize, usize)>, Error> {
+        let type_len = self.field_type_len();
+        let Some(field_type) = buf.get(..type_len) else {
+            return Ok(None);
+        };
+        let field_type = self.read_uint(field_type) as FieldType;
+        Ok(self
+            .read_length(self.field_length, &buf[type_len..])?
+            .map(|(len, len_size)| (field_type, len, type_len + len_size)))
+    }
+
+    pub(crate) fn put_field_header(
+        &self,
+        buf: &mut BytesMut,
+        field_type: FieldType,
+        len: usize,
+    ) -> Result<(), Error> {
+        if len > self.field_length.max() {
+            return Err(Error::new(
+                ErrorCode::ValueTooLarge,
+                format!("Field value of {len} bytes does not fit the field length"),
+            )
+            .with_field_type(field_type));
+        }
+        match self.field_type {
+            FieldTypeWidth::U8 => buf.put_u8(narrow_type(field_type)?),
+            FieldTypeWidth::U16 => self.put_uint(buf, field_type as u64, 2),
+        }
+        self.put_length(buf, self.field_length, len);
+        Ok(())
+    }
+
+    fn read_length(&self, width: LengthWidth, buf: &[u8]) -> Result<Option<(usize, usize)>, Error> {
+        if width == LengthWidth::Varint {
+            return Ok(read_varint(buf)?.map(|(value, len)| (value as usize, len)));
+        }
+        let len = width.len(0);
+        Ok(buf
+            .get(..len)
+            .map(|bytes| (self.read_uint(bytes) as usize, len)))
+    }
+
+    fn put_length(&self, buf: &mut BytesMut, width: LengthWidth, value: usize) {
+        match width {
+            LengthWidth::Varint => put_varint(buf, value as u64),
+            width => self.put_uint(buf, value as u64, width.len(0)),
+        }
+    }
+
+    pub(crate) fn read_u32(&self, buf: &[u8]) -> u32 {
+        self.read_uint(&buf[..4]) as u32
+    }
+
+    pub(crate) fn put_u32(&self, buf: &mut BytesMut, value: u32) {
+        self.put_uint(buf, value as u64, 4);
+    }
+
+    pub(crate) fn read_u64(&self, buf: &[u8]) -> u64 {
+        self.read_uint(buf)
+    }
+
+    pub(crate) fn put_u64(&self, buf: &mut BytesMut, value: u64) {
+        self.put_uint(buf, value, 8);
+    }
+
+    fn read_uint(&self, bytes: &[u8]) -> u64 {
+        let fold = |acc: u64, b: &u8| (acc << 8) | *b as u64;
+        match self.endianness {
+            Endianness::Big => bytes.iter().fold(0, fold),
+            Endianness::Little => bytes.iter().rev().fold(0, fold),
+        }
+    }
+
+    fn put_uint(&self, buf: &mut BytesMut, value: u64, width: usize) {
+        match self.endianness {
+            Endianness::Big => buf.put_uint(value, width),
+            Endianness::Little => buf.put_uint_le(value, width),
+        }
+    }
+}
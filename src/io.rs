@@ -0,0 +1,225 @@
+#[cfg(feature = "auth")]
+use crate::auth::Keyring;
+use crate::error::{Error, ErrorCode};
+use crate::message::Message;
+use crate::wire::WireConfig;
+use bytes::BytesMut;
+use std::io::{self, IoSlice, Read, Write};
+
+/// Reads whole `Message` frames from a blocking `Read` source.
+#[derive(Debug)]
+pub struct TlvReader<R: Read> {
+    inner: R,
+    config: WireConfig,
+    #[cfg(feature = "auth")]
+    keyring: Option<Keyring>,
+}
+
+impl<R: Read> TlvReader<R> {
+    pub fn new(inner: R) -> TlvReader<R> {
+        TlvReader {
+            inner,
+            config: WireConfig::default(),
+            #[cfg(feature = "auth")]
+            keyring: None,
+        }
+    }
+
+    /// Reads frames laid out as described by `config`.
+    pub fn with_config(mut self, config: WireConfig) -> TlvReader<R> {
+        self.config = config;
+        self
+    }
+
+    /// Reads authenticated frames, rejecting any whose tag does not verify
+    /// with `keyring`.
+    #[cfg(feature = "auth")]
+    pub fn with_keyring(mut self, keyring: Keyring) -> TlvReader<R> {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    pub fn get_ref(&self) -> &R {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut R {
+        &mut self.inner
+    }
+
+    pub fn into_inner(self) -> R {
+        self.inner
+    }
+
+    /// Reads the next message.
+    ///
+    /// Returns `Ok(None)` on a clean EOF at a frame boundary and an
+    /// `IncompleteMessage` error when the stream ends inside a frame.
+    pub fn read_message(&mut self) -> Result<Option<Message>, Error> {
+        // A varint prefix is read byte by byte until it is complete
+        let format = *self.config.format();
+        let mut prefix = [0u8; 5];
+        let mut read = 0;
+        let declared_length = loop {
+            let wanted = if format.has_varint_prefix() {
+                read + 1
+            } else {
+                format.min_prefix_len()
+            };
+            let n = self.read_full(&mut prefix[read..wanted])?;
+            if n == 0 && read == 0 {
+                crate::rapid_debug!("Reached end of stream at frame boundary");
+                return Ok(None);
+            }
+            read += n;
+            if read < wanted {
+                return Err(truncated(read, wanted));
+            }
+            if let Some(len) = self.config.frame_len(&prefix[..read])? {
+                break len;
+            }
+        };
+        let prefix = &prefix[..read];
+
+        let mut frame = BytesMut::zeroed(declared_length);
+        frame[..prefix.len()].copy_from_slice(prefix);
+        let read = self.read_full(&mut frame[prefix.len()..])?;
+        if read < declared_length - prefix.len() {
+            return Err(truncated(prefix.len() + read, declared_length));
+        }
+
+        #[cfg(feature = "auth")]
+        if let Some(keyring) = &self.keyring {
+            return keyring.parse(frame.freeze(), &self.config).map(Some);
+        }
+        self.config.parse(frame.freeze()).map(Some)
+    }
+
+    // Like read_exact, but reports how many bytes were read before EOF
+    fn read_full(&mut self, buf: &mut [u8]) -> Result<usize, Error> {
+        let mut read = 0;
+        while read < buf.len() {
+            match self.inner.read(&mut buf[read..]) {
+                Ok(0) => break,
+                Ok(n) => read += n,
+                Err(e) if e.kind() == io::ErrorKind::Interrupted => continue,
+                Err(e) => return Err(Error::new(ErrorCode::ReadFailed, e.to_string())),
+            }
+        }
+        Ok(read)
+    }
+}
+
+impl<R: Read> Iterator for TlvReader<R> {
+    type Item = Result<Message, Error>;
+
+    fn next(&mut self) -> Option<Self::Item> {
+        self.read_message().transpose()
+    }
+}
+
+fn truncated(read: usize, expected: usize) -> Error {
+    crate::rapid_warn!(
+        "Frame reading failed: Stream ended after {} of {} bytes",
+        read,
+        expected
+    );
+    Error::new(
+        ErrorCode::IncompleteMessage,
+        "Stream ended inside a TLV frame".into(),
+    )
+    .at_offset(read)
+}
+
+/// Writes `Message` frames to a blocking `Write` sink.
+///
+/// Each frame goes to the sink in a single vectored write of its generated
+/// headers and the field values, which are not copied. Compressed and
+/// authenticated frames are the exception: they are assembled in a buffer
+/// first. Nothing is buffered between frames; wrap sinks that need fewer,
+/// larger writes in a `BufWriter`, which copies small frames into its buffer.
+#[derive(Debug)]
+pub struct TlvWriter<W: Write> {
+    inner: W,
+    config: WireConfig,
+    #[cfg(feature = "auth")]
+    keyring: Option<Keyring>,
+}
+
+impl<W: Write> TlvWriter<W> {
+    pub fn new(inner: W) -> TlvWriter<W> {
+        TlvWriter {
+            inner,
+            config: WireConfig::default(),
+            #[cfg(feature = "auth")]
+            keyring: None,
+        }
+    }
+
+    /// Writes frames laid out as described by `config`.
+    pub fn with_config(mut self, config: WireConfig) -> TlvWriter<W> {
+        self.config = config;
+        self
+    }
+
+    /// Signs every frame with the keyring's current key.
+    #[cfg(feature = "auth")]
+    pub fn with_keyring(mut self, keyring: Keyring) -> TlvWriter<W> {
+        self.keyring = Some(keyring);
+        self
+    }
+
+    pub fn get_ref(&self) -> &W {
+        &self.inner
+    }
+
+    pub fn get_mut(&mut self) -> &mut W {
+        &mut self.inner
+    }
+
+    /// Flushes the sink and returns it.
+    pub fn into_inner(mut self) -> Result<W, Error> {
+        self.flush()?;
+        Ok(self.inner)
+    }
+
+    pub fn write_message(&mut self, msg: &Message) -> Result<(), Error> {
+        crate::rapid_debug!("Writing message with event_type: {}", msg.event_type);
+        #[cfg(feature = "auth")]
+        if let Some(keyring) = &self.keyring {
+            let frame = keyring.encode(msg, &self.config)?;
+            return self.inner.write_all(&frame).map_err(|e| write_failed(&e));
+        }
+        if let (true, Some(raw)) = (self.config.is_legacy(), msg.cached_bytes()) {
+            return self.inner.write_all(raw).map_err(|e| write_failed(&e));
+        }
+        let frame = self.config.encode_parts(msg)?;
+        write_all_vectored(&mut self.inner, &mut frame.slices()).map_err(|e| write_failed(&e))
+    }
+
+    pub fn flush(&mut self) -> Result<(), Error> {
+        self.inner.flush().map_err(|e| write_failed(&e))
+    }
+}
+
+// Write::write_all_vectored is not stable yet
+fn write_all_vectored<W: Write>(writer: &mut W, mut slices: &mut [IoSlice<'_>]) -> io::Result<()> {
+    while !slices.is_empty() {
+        match writer.write_vectored(slices) {
+            Ok(0) => {
+                return Err(io::Error::new(
+                    io::ErrorKind::WriteZero,
+                    "failed to write whole frame",
+                ));
+            }
+            Ok(n) => IoSlice::advance_slices(&mut slices, n),
+            Err(e) if e.kind() == io::ErrorKind::Interrupted => {}
+            Err(e) => return Err(e),
+        }
+    }
+    Ok(())
+}
+
+fn write_failed(err: &io::Error) -> Error {
+    Error::new(ErrorCode::WriteFailed, err.to_string())
+}
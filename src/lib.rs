@@ -1,10 +1,91 @@
+#[cfg(feature = "auth")]
+mod auth;
+#[cfg(feature = "tokio")]
+mod codec;
+mod compress;
+mod convert;
+#[cfg(feature = "serde")]
+mod de;
+mod decoder;
+#[cfg(feature = "encryption")]
+mod encryption;
 mod error;
 mod field;
+mod format;
+mod io;
+mod limits;
 mod message;
+mod message_ref;
+mod options;
 mod rapid_log;
+mod registry;
+mod schema;
+#[cfg(feature = "serde")]
+mod ser;
+mod wire;
 
+#[cfg(feature = "auth")]
+pub use crate::auth::AUTH_TAG_LEN;
+#[cfg(feature = "auth")]
+pub use crate::auth::Keyring as RapidTlvKeyring;
+#[cfg(feature = "tokio")]
+pub use crate::codec::TlvCodec as RapidTlvCodec;
+pub use crate::compress::Compression as RapidTlvCompression;
+pub use crate::compress::{DEFAULT_COMPRESSION_THRESHOLD, DEFAULT_MAX_DECOMPRESSED_SIZE};
+pub use crate::convert::{TlvMessage, TlvValue};
+#[cfg(feature = "serde")]
+pub use crate::de::from_message;
+pub use crate::decoder::FrameDecoder as RapidTlvFrameDecoder;
+#[cfg(feature = "encryption")]
+pub use crate::encryption::Cipher as RapidTlvCipher;
+#[cfg(feature = "encryption")]
+pub use crate::encryption::ENCRYPTION_KEY_LEN;
+#[cfg(feature = "encryption")]
+pub use crate::encryption::Encryption as RapidTlvEncryption;
 pub use crate::error::Error as RapidTlvError;
+pub use crate::error::ErrorCode as RapidTlvErrorCode;
+pub use crate::error::{
+    EVT_ERROR, FIELD_ERROR_CODE, FIELD_ERROR_FIELD_TYPE, FIELD_ERROR_MESSAGE, FIELD_ERROR_OFFSET,
+};
 pub use crate::field::Field as RapidTlvField;
 pub use crate::field::FieldType as RapidTlvFieldType;
+pub use crate::format::Endianness as RapidTlvEndianness;
+pub use crate::format::FieldTypeWidth as RapidTlvFieldTypeWidth;
+pub use crate::format::LengthWidth as RapidTlvLengthWidth;
+pub use crate::format::WireFormat as RapidTlvWireFormat;
+pub use crate::io::TlvReader as RapidTlvReader;
+pub use crate::io::TlvWriter as RapidTlvWriter;
+pub use crate::limits::ParseLimits as RapidTlvParseLimits;
+pub use crate::limits::{DEFAULT_MAX_FIELD_COUNT, DEFAULT_MAX_FRAME_SIZE};
+pub use crate::message::DEFAULT_MAX_NESTING_DEPTH;
 pub use crate::message::EventType as RapidTlvEventType;
+pub use crate::message::FieldOrder as RapidTlvFieldOrder;
 pub use crate::message::Message as RapidTlvMessage;
+pub use crate::message_ref::FieldRef as RapidTlvFieldRef;
+pub use crate::message_ref::MessageRef as RapidTlvMessageRef;
+pub use crate::options::ParseOptions as RapidTlvParseOptions;
+pub use crate::options::ParseReport as RapidTlvParseReport;
+pub use crate::registry::SchemaRegistry as RapidTlvSchemaRegistry;
+pub use crate::registry::ValidationError as RapidTlvValidationError;
+pub use crate::schema::EventField as RapidTlvEventField;
+pub use crate::schema::EventSchema as RapidTlvEventSchema;
+pub use crate::schema::FieldSchema as RapidTlvFieldSchema;
+pub use crate::schema::Presence as RapidTlvPresence;
+pub use crate::schema::Schema as RapidTlvSchema;
+pub use crate::schema::ValueType as RapidTlvValueType;
+#[cfg(feature = "serde")]
+pub use crate::ser::to_message;
+pub use crate::wire::WireConfig as RapidTlvWireConfig;
+pub use crate::wire::{
+    FLAG_CHECKSUMMED, FLAG_COMPRESSED, FLAG_CORRELATION_ID, LATEST_WIRE_VERSION,
+};
+
+#[cfg(feature = "derive")]
+pub use rapid_tlv_derive::TlvMessage;
+
+// Used by code generated from `#[derive(TlvMessage)]` and `rapid_tlv_build`
+#[doc(hidden)]
+pub mod __private {
+    pub use crate::convert::{missing_field, unexpected_event_type, unknown_event_type};
+    pub use bytes::Bytes;
+}
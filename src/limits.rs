@@ -0,0 +1,149 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::FieldType;
+use crate::message::DEFAULT_MAX_NESTING_DEPTH;
+
+/// Largest frame accepted by default.
+pub const DEFAULT_MAX_FRAME_SIZE: usize = 16 * 1024 * 1024;
+/// Most fields accepted in one message by default.
+pub const DEFAULT_MAX_FIELD_COUNT: usize = 64 * 1024;
+
+/// Bounds on what parsers accept from untrusted input.
+///
+/// Lengths are checked as soon as they are read, before anything is
+/// allocated for them, and violations fail with `ValueTooLarge`. The
+/// defaults allow frames and field values of up to 16 MiB, 65536 fields per
+/// message and `DEFAULT_MAX_NESTING_DEPTH` levels of nested messages.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Hash)]
+pub struct ParseLimits {
+    max_frame_size: usize,
+    max_field_size: usize,
+    max_field_count: usize,
+    max_nesting_depth: u8,
+}
+
+impl Default for ParseLimits {
+    fn default() -> Self {
+        ParseLimits {
+            max_frame_size: DEFAULT_MAX_FRAME_SIZE,
+            max_field_size: DEFAULT_MAX_FRAME_SIZE,
+            max_field_count: DEFAULT_MAX_FIELD_COUNT,
+            max_nesting_depth: DEFAULT_MAX_NESTING_DEPTH,
+        }
+    }
+}
+
+impl ParseLimits {
+    pub fn new() -> ParseLimits {
+        ParseLimits::default()
+    }
+
+    /// No limits beyond what the wire format can express, for trusted input.
+    pub fn unlimited() -> ParseLimits {
+        ParseLimits {
+            max_frame_size: usize::MAX,
+            max_field_size: usize::MAX,
+            max_field_count: usize::MAX,
+            max_nesting_depth: u8::MAX,
+        }
+    }
+
+    /// Largest frame, length prefix included.
+    pub fn max_frame_size(&self) -> usize {
+        self.max_frame_size
+    }
+
+    pub fn with_max_frame_size(mut self, size: usize) -> ParseLimits {
+        self.max_frame_size = size;
+        self
+    }
+
+    /// Largest single field value.
+    pub fn max_field_size(&self) -> usize {
+        self.max_field_size
+    }
+
+    pub fn with_max_field_size(mut self, size: usize) -> ParseLimits {
+        self.max_field_size = size;
+        self
+    }
+
+    pub fn max_field_count(&self) -> usize {
+        self.max_field_count
+    }
+
+    pub fn with_max_field_count(mut self, count: usize) -> ParseLimits {
+        self.max_field_count = count;
+        self
+    }
+
+    /// Levels of nested messages parsed below the outermost one.
+    pub fn max_nesting_depth(&self) -> u8 {
+        self.max_nesting_depth
+    }
+
+    pub fn with_max_nesting_depth(mut self, depth: u8) -> ParseLimits {
+        self.max_nesting_depth = depth;
+        self
+    }
+
+    pub(crate) fn check_frame_size(&self, len: usize) -> Result<(), Error> {
+        if len > self.max_frame_size {
+            crate::rapid_warn!(
+                "Frame of {} bytes exceeds maximum frame size of {} bytes",
+                len,
+                self.max_frame_size
+            );
+            return Err(Error::new(
+                ErrorCode::ValueTooLarge,
+                format!(
+                    "Frame of {len} bytes exceeds maximum frame size of {} bytes",
+                    self.max_frame_size
+                ),
+            )
+            .at_offset(0));
+        }
+        Ok(())
+    }
+
+    /// Checks the `count`-th field, whose header starts at `offset`.
+    pub(crate) fn check_field(
+        &self,
+        field_type: FieldType,
+        len: usize,
+        count: usize,
+        offset: usize,
+    ) -> Result<(), Error> {
+        if count > self.max_field_count {
+            crate::rapid_warn!(
+                "Message exceeds maximum field count of {}",
+                self.max_field_count
+            );
+            return Err(Error::new(
+                ErrorCode::ValueTooLarge,
+                format!(
+                    "Message has more than the maximum of {} fields",
+                    self.max_field_count
+                ),
+            )
+            .at_offset(offset));
+        }
+        if len > self.max_field_size {
+            crate::rapid_warn!(
+                "Field type {} of {} bytes exceeds maximum field size of {} bytes",
+                field_type,
+                len,
+                self.max_field_size
+            );
+            return Err(Error::new(
+                ErrorCode::ValueTooLarge,
+                format!(
+                    "Field of {len} bytes exceeds maximum field size of {} bytes",
+                    self.max_field_size
+                ),
+            )
+            .at_offset(offset)
+            .with_field_type(field_type));
+        }
+        Ok(())
+    }
+}
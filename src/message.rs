@@ -1,15 +1,52 @@
+#[cfg(feature = "auth")]
+use crate::auth::Keyring;
 use crate::error::{Error, ErrorCode};
-use crate::field::{Field, FieldType};
+use crate::field::{Field, FieldType, narrow_type};
+use crate::limits::ParseLimits;
+use crate::message_ref::MessageRef;
+use crate::options::{ParseOptions, ParseReport};
+use crate::wire::WireConfig;
 use bytes::{BufMut, Bytes, BytesMut};
+use sha2::{Digest, Sha256};
+use std::hash::{Hash, Hasher};
+use std::io::Write;
 
 pub type EventType = u8;
 
+// Marks a field type whose last position must be searched for
+const SCAN: u8 = u8::MAX;
+
+/// Default limit for `get_message_field` recursion.
+pub const DEFAULT_MAX_NESTING_DEPTH: u8 = 16;
+
+/// Order in which `encode` and `fields` emit a message's fields.
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum FieldOrder {
+    /// Ascending field type; repeated values keep their relative order.
+    #[default]
+    ByType,
+    /// The order in which fields were added or appeared on the wire.
+    Insertion,
+}
+
 #[derive(Debug, Clone)]
 pub struct Message {
     raw_data: Bytes,
 
     pub event_type: EventType,
-    fields: [Option<Field>; 256],
+    // every field in insertion / wire order
+    fields: Vec<Field>,
+    // per field type below 256, 1 + position of its last value in `fields`,
+    // 0 if absent or SCAN once the position no longer fits
+    last: [u8; 256],
+    // true while insertion order is also ascending by field type
+    sorted: bool,
+    order: FieldOrder,
+    // nesting level of this message below the outermost one
+    depth: u8,
+    // applied to nested messages parsed from this one
+    limits: ParseLimits,
+    correlation_id: Option<u64>,
 }
 
 impl Message {
@@ -18,149 +55,680 @@ impl Message {
         Message {
             raw_data: Bytes::new(),
             event_type,
-            fields: [(); 256].map(|_| None),
+            fields: Vec::new(),
+            last: [0; 256],
+            sorted: true,
+            order: FieldOrder::ByType,
+            depth: 0,
+            limits: ParseLimits::default(),
+            correlation_id: None,
         }
     }
 
+    /// Parses one complete frame within the default `ParseLimits`.
     pub fn parse(raw: Bytes) -> Result<Message, Error> {
+        Message::parse_with_limits(raw, &ParseLimits::default())
+    }
+
+    /// Parses one complete frame within `limits`, which also apply to nested
+    /// messages read from it.
+    pub fn parse_with_limits(raw: Bytes, limits: &ParseLimits) -> Result<Message, Error> {
         crate::rapid_debug!("Parsing message from {} bytes", raw.len());
-        if raw.len() < 5 {
-            crate::rapid_warn!(
-                "Message parsing failed: Not enough data for TLV header (only {} bytes)",
-                raw.len()
-            );
-            return Err(Error::new(
-                ErrorCode::Malformed,
-                "Not enough data for TLV header".into(),
-            ));
-        }
+        let view = MessageRef::parse_with_limits(&raw, limits)?;
+        let mut msg = Message::from_ref(&view, raw.clone());
+        msg.limits = *limits;
 
-        // Check if the declared message length matches the actual length
-        let declared_length = u32::from_be_bytes(raw[0..4].try_into().unwrap()) as usize;
-        if declared_length != raw.len() {
-            crate::rapid_warn!(
-                "Message parsing failed: Declared length ({}) doesn't match actual length ({})",
-                declared_length,
-                raw.len()
-            );
-            return Err(Error::new(
-                ErrorCode::Malformed,
-                "Malformed tlv field: Declared length doesn't match actual length".into(),
-            ));
-        }
+        crate::rapid_debug!(
+            "Message parsing completed successfully with event_type: {} and {} fields",
+            msg.event_type,
+            msg.field_count()
+        );
+        Ok(msg)
+    }
 
-        let mut msg = Message {
-            raw_data: Bytes::new(),
-            event_type: raw[4],
-            fields: std::array::from_fn(|_| None),
-        };
+    /// Parses one complete frame, applying `options` to its fields, and
+    /// reports what a lenient parse tolerated.
+    pub fn parse_with_options(
+        raw: Bytes,
+        options: &ParseOptions,
+    ) -> Result<(Message, ParseReport), Error> {
+        Message::parse_inspected(raw, &ParseLimits::default(), options)
+    }
 
-        let mut offset = 5;
+    pub(crate) fn parse_inspected(
+        raw: Bytes,
+        limits: &ParseLimits,
+        options: &ParseOptions,
+    ) -> Result<(Message, ParseReport), Error> {
+        let view = MessageRef::parse_with_limits(&raw, limits)?;
+        let mut inspector = options.inspector();
+        let mut end = 5;
+        for field in view.fields() {
+            let value = field.value_range();
+            inspector.field(field.field_type(), value.start - 5)?;
+            end = value.end;
+        }
+        let report = inspector.finish(end, raw.len())?;
 
-        while offset + 5 <= raw.len() {
-            let field_typ = raw[offset];
-            offset += 1;
+        let mut msg = Message::from_ref(&view, raw.clone());
+        msg.limits = *limits;
+        Ok((msg, report))
+    }
 
-            let length = u32::from_be_bytes(raw[offset..offset + 4].try_into().unwrap()) as usize;
-            offset += 4;
+    /// Parses a frame laid out as described by `config`.
+    pub fn parse_with(raw: Bytes, config: &WireConfig) -> Result<Message, Error> {
+        config.parse(raw)
+    }
 
-            if offset + length > raw.len() {
-                crate::rapid_warn!(
-                    "Message parsing failed: Not enough data for field value (offset: {}, length: {}, total: {})",
-                    offset,
-                    length,
-                    raw.len()
-                );
-                return Err(Error::new(
-                    ErrorCode::Malformed,
-                    "Not enough data for field value".into(),
-                ));
-            }
+    /// Parses a frame written by `to_bytes_authenticated`, rejecting it with
+    /// `AuthenticationFailed` unless its tag matches the key it names.
+    #[cfg(feature = "auth")]
+    pub fn parse_authenticated(raw: Bytes, keyring: &Keyring) -> Result<Message, Error> {
+        keyring.parse(raw, &WireConfig::default())
+    }
 
-            let value = raw.slice(offset..offset + length);
-            offset += length;
+    /// Parses an authenticated frame written by `to_bytes_authenticated_with`
+    /// with the same `config`.
+    #[cfg(feature = "auth")]
+    pub fn parse_authenticated_with(
+        raw: Bytes,
+        keyring: &Keyring,
+        config: &WireConfig,
+    ) -> Result<Message, Error> {
+        keyring.parse(raw, config)
+    }
 
-            msg.fields[field_typ as usize] = Some(Field::new(field_typ, value));
+    /// Builds a message from a validated view; `raw` must hold the same bytes.
+    pub(crate) fn from_ref(view: &MessageRef<'_>, raw: Bytes) -> Message {
+        let mut msg = Message {
+            raw_data: Bytes::new(),
+            event_type: view.event_type(),
+            fields: Vec::new(),
+            last: [0; 256],
+            sorted: true,
+            order: FieldOrder::ByType,
+            depth: 0,
+            limits: ParseLimits::default(),
+            correlation_id: None,
+        };
+
+        for field in view.fields() {
+            msg.push_field(Field::new(
+                field.field_type(),
+                raw.slice(field.value_range()),
+            ));
         }
 
         msg.raw_data = raw;
-
-        crate::rapid_debug!(
-            "Message parsing completed successfully with event_type: {} and {} fields",
-            msg.event_type,
-            msg.fields.iter().filter(|f| f.is_some()).count()
-        );
-        Ok(msg)
+        msg
     }
 
+    /// Returns the field of the given type. If the type occurs more than once,
+    /// the last occurrence wins, matching a repeated `add_field`.
     pub fn get_field(&self, field_type: &FieldType) -> Option<&Field> {
-        self.fields[*field_type as usize].as_ref()
+        match self.last_entry(*field_type) {
+            0 => None,
+            SCAN => self
+                .fields
+                .iter()
+                .rev()
+                .find(|f| f.field_type() == field_type),
+            last => self.fields.get(last as usize - 1),
+        }
+    }
+
+    /// Returns every field of the given type in insertion / wire order.
+    pub fn get_fields(&self, field_type: &FieldType) -> impl Iterator<Item = &Field> {
+        let candidates: &[Field] = if self.has_field_type(*field_type) {
+            &self.fields
+        } else {
+            &[]
+        };
+        candidates
+            .iter()
+            .filter(move |f| f.field_type() == field_type)
     }
 
+    /// Sets the field of the given type, replacing all existing values of that type.
     pub fn add_field(&mut self, field_type: FieldType, value: Bytes) {
         crate::rapid_debug!(
             "Adding field type {} with {} bytes to message",
             field_type,
             value.len()
         );
-        self.fields[field_type as usize] = Option::from(Field::new(field_type, value));
+        self.replace_field(Field::new(field_type, value));
+    }
+
+    /// Appends another value for the given field type, keeping existing ones.
+    pub fn add_repeated_field(&mut self, field_type: FieldType, value: Bytes) {
+        crate::rapid_debug!(
+            "Appending repeated field type {} with {} bytes to message",
+            field_type,
+            value.len()
+        );
+        self.push_field(Field::new(field_type, value));
 
         self.raw_data = Bytes::new();
     }
 
+    pub fn with_repeated_field(mut self, field_type: FieldType, value: Bytes) -> Self {
+        self.add_repeated_field(field_type, value);
+        self
+    }
+
     pub fn with_field(mut self, field_type: FieldType, value: Bytes) -> Self {
         crate::rapid_debug!(
             "Adding field type {} with {} bytes to message (builder style)",
             field_type,
             value.len()
         );
-        self.fields[field_type as usize] = Option::from(Field::new(field_type, value));
-
-        self.raw_data = Bytes::new();
+        self.replace_field(Field::new(field_type, value));
         self
     }
 
+    /// Removes all values of the given field type.
     pub fn remove_field(&mut self, field_type: FieldType) -> bool {
         crate::rapid_debug!("Removing field type {} from message", field_type);
-        let had_field = self.fields[field_type as usize].is_some();
-        self.fields[field_type as usize] = None;
+        let had_field = self.drop_field_type(field_type);
 
         self.raw_data = Bytes::new();
         had_field
     }
 
+    // Types above the table are always searched for
+    fn last_entry(&self, field_type: FieldType) -> u8 {
+        self.last.get(field_type as usize).copied().unwrap_or(SCAN)
+    }
+
+    fn has_field_type(&self, field_type: FieldType) -> bool {
+        match self.last.get(field_type as usize) {
+            Some(last) => *last != 0,
+            None => self.fields.iter().any(|f| *f.field_type() == field_type),
+        }
+    }
+
+    fn set_last(&mut self, field_type: FieldType, len: usize) {
+        if let Some(last) = self.last.get_mut(field_type as usize) {
+            *last = u8::try_from(len).unwrap_or(SCAN);
+        }
+    }
+
+    fn push_field(&mut self, field: Field) {
+        let field_type = *field.field_type();
+        if let Some(last) = self.fields.last() {
+            self.sorted &= *last.field_type() <= field_type;
+        }
+        self.fields.push(field);
+        self.set_last(field_type, self.fields.len());
+    }
+
+    fn drop_field_type(&mut self, field_type: FieldType) -> bool {
+        if !self.has_field_type(field_type) {
+            return false;
+        }
+        self.fields.retain(|f| *f.field_type() != field_type);
+        // Values after the removed ones have moved
+        self.last = [0; 256];
+        for pos in 0..self.fields.len() {
+            self.set_last(*self.fields[pos].field_type(), pos + 1);
+        }
+        true
+    }
+
+    // A replaced field counts as newly inserted, so it moves to the end in insertion order
+    fn replace_field(&mut self, field: Field) {
+        self.drop_field_type(*field.field_type());
+        self.push_field(field);
+
+        self.raw_data = Bytes::new();
+    }
+
+    pub fn field_order(&self) -> FieldOrder {
+        self.order
+    }
+
+    /// Selects the order used by `encode` and `fields`. Parsed messages remember
+    /// their wire order, so `FieldOrder::Insertion` reproduces it byte for byte.
+    pub fn set_field_order(&mut self, order: FieldOrder) {
+        if self.order != order {
+            self.order = order;
+            self.raw_data = Bytes::new();
+        }
+    }
+
+    pub fn with_field_order(mut self, order: FieldOrder) -> Self {
+        self.set_field_order(order);
+        self
+    }
+
+    /// Iterates over all present fields in the message's `FieldOrder`.
+    pub fn fields(&self) -> impl Iterator<Item = &Field> + '_ {
+        if self.order == FieldOrder::Insertion {
+            Fields::InOrder(self.fields.iter())
+        } else {
+            self.fields_by_type()
+        }
+    }
+
+    /// Number of fields in the message, counting every repeated value.
+    pub fn field_count(&self) -> usize {
+        self.fields.len()
+    }
+
+    fn encoded_len(&self) -> usize {
+        let mut len = 5; //  length 4 bytes + event_type 1 byte
+        for field in self.fields() {
+            len += field.len();
+        }
+        len
+    }
+
+    /// The encoding kept from parsing or the last `encode`, if still valid.
+    pub(crate) fn cached_bytes(&self) -> Option<&Bytes> {
+        (!self.raw_data.is_empty()).then_some(&self.raw_data)
+    }
+
     pub fn encode(&mut self) -> Result<&[u8], Error> {
         crate::rapid_debug!("Encoding message with event_type: {}", self.event_type);
         // Only rebuild raw_data if it has been modified or is empty
         if self.raw_data.is_empty() {
-            let mut buffer_len = 5; //  length 4 bytes + event_type 1 byte
+            self.raw_data = self.build()?;
+        }
+
+        Ok(self.raw_data.as_ref())
+    }
+
+    /// Returns the encoded message as shared `Bytes`; free when the encoding
+    /// is already cached, e.g. for a parsed message.
+    pub fn to_bytes(&self) -> Result<Bytes, Error> {
+        if !self.raw_data.is_empty() {
+            return Ok(self.raw_data.clone());
+        }
+        self.build()
+    }
+
+    /// Encodes the message laid out as described by `config`.
+    pub fn to_bytes_with(&self, config: &WireConfig) -> Result<Bytes, Error> {
+        config.encode(self)
+    }
+
+    /// Encodes the message followed by the id of the keyring's current key
+    /// and an HMAC-SHA256 tag over the whole frame.
+    #[cfg(feature = "auth")]
+    pub fn to_bytes_authenticated(&self, keyring: &Keyring) -> Result<Bytes, Error> {
+        keyring.encode(self, &WireConfig::default())
+    }
+
+    /// Encodes the message laid out as described by `config`, then appends
+    /// the key id and tag; the length prefix counts both.
+    #[cfg(feature = "auth")]
+    pub fn to_bytes_authenticated_with(
+        &self,
+        keyring: &Keyring,
+        config: &WireConfig,
+    ) -> Result<Bytes, Error> {
+        keyring.encode(self, config)
+    }
+
+    /// Id that pairs a request with its response.
+    ///
+    /// Only sent with a versioned header; the legacy header has no room for it.
+    pub fn correlation_id(&self) -> Option<u64> {
+        self.correlation_id
+    }
+
+    pub fn set_correlation_id(&mut self, correlation_id: Option<u64>) {
+        self.correlation_id = correlation_id;
+    }
+
+    pub fn with_correlation_id(mut self, correlation_id: u64) -> Self {
+        self.correlation_id = Some(correlation_id);
+        self
+    }
+
+    fn build(&self) -> Result<Bytes, Error> {
+        // berechne Länge aller Felder
+        let buffer_len = self.encoded_len();
+
+        // erstelle Buffer mit der richtigen Länge
+        let mut buffer = BytesMut::with_capacity(buffer_len);
+
+        // 1. schreibe Gesamtlänge (ink. event_type + Felder)
+        buffer.put_u32(buffer_len as u32);
 
-            // berechne Länge aller Felder
-            for field in self.fields.iter().flatten() {
-                buffer_len += field.len();
+        // 2. schreibe EventType
+        buffer.put_u8(self.event_type);
+
+        // 3. Schreibe alle Felder direkt in Buffer
+        for field in self.fields() {
+            buffer.put(field.encode()?)
+        }
+
+        let raw = buffer.freeze(); // Bytes ist nun immutable view
+        crate::rapid_debug!(
+            "Message encoded successfully, total size: {} bytes",
+            raw.len()
+        );
+        Ok(raw)
+    }
+
+    /// Encodes the message in canonical form: the legacy header, fields in
+    /// ascending type and every field type at most once. Equal messages
+    /// always encode to the same bytes, whatever their field order, cached
+    /// encoding or correlation id.
+    ///
+    /// Fails with `Malformed` if a field type is repeated and with
+    /// `ValueTooLarge` if one is above 255. Field values are opaque and kept
+    /// as they are, including nested messages.
+    pub fn encode_canonical(&self) -> Result<Bytes, Error> {
+        let mut previous = None;
+        for field in self.fields_by_type() {
+            let field_type = *field.field_type();
+            narrow_type(field_type)?;
+            if previous == Some(field_type) {
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    format!("Repeated field type {field_type} has no canonical encoding"),
+                )
+                .with_field_type(field_type));
             }
+            previous = Some(field_type);
+        }
 
-            // erstelle Buffer mit der richtigen Länge
-            let mut buffer = BytesMut::with_capacity(buffer_len);
+        let mut buffer = BytesMut::with_capacity(self.encoded_len()).writer();
+        self.write_canonical(&mut buffer)
+            .expect("writing to BytesMut cannot fail");
+        Ok(buffer.into_inner().freeze())
+    }
 
-            // 1. schreibe Gesamtlänge (ink. event_type + Felder)
-            buffer.put_u32(buffer_len as u32);
+    /// Whether `raw` is exactly one frame in the form `encode_canonical`
+    /// writes.
+    pub fn is_canonical(raw: &[u8]) -> bool {
+        let Ok(view) = MessageRef::parse_with_limits(raw, &ParseLimits::unlimited()) else {
+            return false;
+        };
+        let mut end = 5;
+        let mut previous = None;
+        for field in view.fields() {
+            if previous.is_some_and(|previous| previous >= field.field_type()) {
+                return false;
+            }
+            previous = Some(field.field_type());
+            end = field.value_range().end;
+        }
+        end == raw.len()
+    }
 
-            // 2. schreibe EventType
-            buffer.put_u8(self.event_type);
+    /// SHA-256 of the canonical encoding, stable across processes and
+    /// versions of this crate.
+    ///
+    /// Repeated values of a field type are hashed in their relative order,
+    /// as `encode_canonical` would write them if repetition were allowed.
+    /// Messages with field types above 255 are hashed with two-byte field
+    /// types and a zero length prefix, which sets them apart from any
+    /// canonical frame.
+    pub fn digest(&self) -> [u8; 32] {
+        let mut hasher = Sha256::new();
+        self.write_canonical(&mut hasher)
+            .expect("hashing cannot fail");
+        hasher.finalize().into()
+    }
 
-            // 3. Schreibe alle Felder direkt in Buffer
-            for field in self.fields.iter().flatten() {
-                buffer.put(field.encode()?)
+    fn fields_by_type(&self) -> Fields<'_> {
+        if self.sorted {
+            Fields::InOrder(self.fields.iter())
+        } else {
+            Fields::ByType {
+                fields: &self.fields,
+                last: &self.last,
+                next_type: 0,
+                current: None,
+                pos: 0,
             }
+        }
+    }
+
+    // Field types above 255 have no canonical encoding; they are written in
+    // two bytes behind a zero length, which no canonical frame has
+    fn write_canonical<W: Write>(&self, writer: &mut W) -> std::io::Result<()> {
+        let wide = self
+            .fields
+            .iter()
+            .any(|f| narrow_type(*f.field_type()).is_err());
+        let len = if wide { 0 } else { self.encoded_len() as u32 };
+        writer.write_all(&len.to_be_bytes())?;
+        writer.write_all(&[self.event_type])?;
+        for field in self.fields_by_type() {
+            let field_type = field.field_type().to_be_bytes();
+            writer.write_all(if wide { &field_type } else { &field_type[1..] })?;
+            writer.write_all(&(field.value().len() as u32).to_be_bytes())?;
+            writer.write_all(field.value())?;
+        }
+        Ok(())
+    }
+}
+
+/// Messages are equal if they have the same event type and the same fields,
+/// compared in canonical order. The cached encoding, `FieldOrder`, limits and
+/// correlation id are not part of a message's content.
+impl PartialEq for Message {
+    fn eq(&self, other: &Message) -> bool {
+        self.event_type == other.event_type
+            && self.fields.len() == other.fields.len()
+            && self.fields_by_type().eq(other.fields_by_type())
+    }
+}
+
+impl Eq for Message {}
+
+/// Hashes the event type and fields in canonical order, consistent with
+/// `PartialEq`.
+impl Hash for Message {
+    fn hash<H: Hasher>(&self, state: &mut H) {
+        state.write_u8(self.event_type);
+        for field in self.fields_by_type() {
+            field.hash(state);
+        }
+    }
+}
+
+impl Message {
+    /// Encodes `message` and stores it as the value of the given field type,
+    /// replacing existing values of that type.
+    pub fn add_message_field(
+        &mut self,
+        field_type: FieldType,
+        message: Message,
+    ) -> Result<(), Error> {
+        crate::rapid_debug!(
+            "Adding nested message with event_type {} as field type {}",
+            message.event_type,
+            field_type
+        );
+        self.add_field(field_type, message.to_bytes()?);
+        Ok(())
+    }
 
-            self.raw_data = buffer.freeze(); // Bytes ist nun immutable view
-            crate::rapid_debug!(
-                "Message encoded successfully, total size: {} bytes",
-                self.raw_data.len()
+    pub fn with_message_field(
+        mut self,
+        field_type: FieldType,
+        message: Message,
+    ) -> Result<Self, Error> {
+        self.add_message_field(field_type, message)?;
+        Ok(self)
+    }
+
+    /// Appends `message` as another value of the given field type.
+    pub fn add_repeated_message_field(
+        &mut self,
+        field_type: FieldType,
+        message: Message,
+    ) -> Result<(), Error> {
+        self.add_repeated_field(field_type, message.to_bytes()?);
+        Ok(())
+    }
+
+    /// Parses the given field as a nested message; `Ok(None)` if it is absent.
+    ///
+    /// The nested message shares this message's buffer instead of copying it.
+    /// Fails once the nesting depth would exceed `max_nesting_depth`.
+    pub fn get_message_field(&self, field_type: FieldType) -> Result<Option<Message>, Error> {
+        self.get_field(&field_type)
+            .map(|field| self.parse_nested(field))
+            .transpose()
+    }
+
+    /// Parses every value of the given field type as a nested message, in order.
+    pub fn get_message_fields(
+        &self,
+        field_type: FieldType,
+    ) -> impl Iterator<Item = Result<Message, Error>> + '_ {
+        self.fields
+            .iter()
+            .filter(move |f| *f.field_type() == field_type)
+            .map(|field| self.parse_nested(field))
+    }
+
+    pub(crate) fn parse_nested(&self, field: &Field) -> Result<Message, Error> {
+        let field_type = *field.field_type();
+        let max_depth = self.limits.max_nesting_depth();
+        if self.depth >= max_depth {
+            crate::rapid_warn!(
+                "Nested message in field type {} exceeds maximum nesting depth of {}",
+                field_type,
+                max_depth
             );
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                format!("Maximum nesting depth of {max_depth} exceeded"),
+            )
+            .with_field_type(field_type));
         }
 
-        Ok(self.raw_data.as_ref())
+        let mut nested = Message::parse_with_limits(field.bytes().clone(), &self.limits)
+            .map_err(|e| e.with_field_type(field_type))?;
+        nested.depth = self.depth + 1;
+        Ok(nested)
+    }
+
+    pub(crate) fn set_limits(&mut self, limits: ParseLimits) {
+        self.limits = limits;
+    }
+
+    pub(crate) fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    /// Nesting level of this message below the outermost one.
+    pub(crate) fn depth(&self) -> u8 {
+        self.depth
+    }
+
+    pub fn max_nesting_depth(&self) -> u8 {
+        self.limits.max_nesting_depth()
+    }
+
+    /// Limits how many levels of nested messages `get_message_field` will parse
+    /// below the outermost message. Nested messages inherit the limit.
+    pub fn set_max_nesting_depth(&mut self, max_depth: u8) {
+        self.limits = self.limits.with_max_nesting_depth(max_depth);
+    }
+}
+
+// Typed accessors that read or replace a single field value
+macro_rules! typed_accessors {
+    ($($get:ident, $set:ident, $ty:ty;)*) => {
+        $(
+            /// Reads the field as a big-endian value; `Ok(None)` if it is absent.
+            pub fn $get(&self, field_type: FieldType) -> Result<Option<$ty>, Error> {
+                self.get_field(&field_type).map(Field::$get).transpose()
+            }
+
+            pub fn $set(&mut self, field_type: FieldType, value: $ty) {
+                self.add_field(field_type, Bytes::copy_from_slice(&value.to_be_bytes()));
+            }
+        )*
+    };
+}
+
+impl Message {
+    typed_accessors! {
+        get_u8, set_u8, u8;
+        get_u16, set_u16, u16;
+        get_u32, set_u32, u32;
+        get_u64, set_u64, u64;
+        get_i64, set_i64, i64;
+        get_f64, set_f64, f64;
+    }
+
+    pub fn get_bool(&self, field_type: FieldType) -> Result<Option<bool>, Error> {
+        self.get_field(&field_type).map(Field::get_bool).transpose()
+    }
+
+    pub fn set_bool(&mut self, field_type: FieldType, value: bool) {
+        self.add_field(field_type, Bytes::copy_from_slice(&[value as u8]));
+    }
+
+    pub fn get_str(&self, field_type: FieldType) -> Result<Option<&str>, Error> {
+        self.get_field(&field_type).map(Field::get_str).transpose()
+    }
+
+    pub fn set_str(&mut self, field_type: FieldType, value: &str) {
+        self.add_field(field_type, Bytes::copy_from_slice(value.as_bytes()));
+    }
+}
+
+enum Fields<'a> {
+    InOrder(std::slice::Iter<'a, Field>),
+    // Walks the present field types in ascending order, scanning for each one
+    ByType {
+        fields: &'a [Field],
+        last: &'a [u8; 256],
+        next_type: usize,
+        current: Option<FieldType>,
+        pos: usize,
+    },
+}
+
+impl<'a> Iterator for Fields<'a> {
+    type Item = &'a Field;
+
+    fn next(&mut self) -> Option<&'a Field> {
+        match self {
+            Fields::InOrder(iter) => iter.next(),
+            Fields::ByType {
+                fields,
+                last,
+                next_type,
+                current,
+                pos,
+            } => loop {
+                if let Some(field_type) = *current {
+                    while *pos < fields.len() {
+                        let field = &fields[*pos];
+                        *pos += 1;
+                        if *field.field_type() == field_type {
+                            return Some(field);
+                        }
+                    }
+                }
+
+                let below_256 = last
+                    .get(*next_type..)
+                    .and_then(|rest| rest.iter().position(|l| *l != 0));
+                let field_type = match below_256 {
+                    Some(i) => *next_type + i,
+                    // Types above the table, smallest first
+                    None => fields
+                        .iter()
+                        .map(|f| *f.field_type() as usize)
+                        .filter(|t| *t >= (*next_type).max(last.len()))
+                        .min()?,
+                };
+                *next_type = field_type + 1;
+                *current = Some(field_type as FieldType);
+                *pos = 0;
+            },
+        }
     }
 }
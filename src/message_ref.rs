@@ -0,0 +1,181 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::FieldType;
+use crate::limits::ParseLimits;
+use crate::message::{EventType, Message};
+use bytes::Bytes;
+use std::ops::Range;
+
+/// Borrowed view of a validated TLV frame.
+///
+/// Field lookups return slices into the original buffer, so reading a
+/// `MessageRef` never allocates.
+#[derive(Debug, Clone, Copy)]
+pub struct MessageRef<'a> {
+    raw: &'a [u8],
+}
+
+/// A single field inside a `MessageRef`.
+#[derive(Debug, Clone, Copy)]
+pub struct FieldRef<'a> {
+    field_type: FieldType,
+    value: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> FieldRef<'a> {
+    pub fn field_type(&self) -> FieldType {
+        self.field_type
+    }
+
+    pub fn value(&self) -> &'a [u8] {
+        self.value
+    }
+
+    // Position of the value within the frame
+    pub(crate) fn value_range(&self) -> Range<usize> {
+        self.offset..self.offset + self.value.len()
+    }
+}
+
+impl<'a> MessageRef<'a> {
+    /// Validates `raw` as one complete frame within the default `ParseLimits`.
+    pub fn parse(raw: &'a [u8]) -> Result<MessageRef<'a>, Error> {
+        MessageRef::parse_with_limits(raw, &ParseLimits::default())
+    }
+
+    /// Validates `raw` as one complete frame within `limits`.
+    pub fn parse_with_limits(raw: &'a [u8], limits: &ParseLimits) -> Result<MessageRef<'a>, Error> {
+        crate::rapid_debug!("Parsing message view from {} bytes", raw.len());
+        if raw.len() < 5 {
+            crate::rapid_warn!(
+                "Message parsing failed: Not enough data for TLV header (only {} bytes)",
+                raw.len()
+            );
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                "Not enough data for TLV header".into(),
+            )
+            .at_offset(0));
+        }
+
+        // Check if the declared message length matches the actual length
+        let declared_length = u32::from_be_bytes(raw[0..4].try_into().unwrap()) as usize;
+        limits.check_frame_size(declared_length)?;
+        if declared_length != raw.len() {
+            crate::rapid_warn!(
+                "Message parsing failed: Declared length ({}) doesn't match actual length ({})",
+                declared_length,
+                raw.len()
+            );
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                "Malformed tlv field: Declared length doesn't match actual length".into(),
+            )
+            .at_offset(0));
+        }
+
+        let mut offset = 5;
+        let mut count = 0;
+
+        while offset + 5 <= raw.len() {
+            let field_start = offset;
+            let field_typ = FieldType::from(raw[offset]);
+            offset += 1;
+
+            let length = u32::from_be_bytes(raw[offset..offset + 4].try_into().unwrap()) as usize;
+            offset += 4;
+            count += 1;
+            limits.check_field(field_typ, length, count, field_start)?;
+
+            if length > raw.len() - offset {
+                crate::rapid_warn!(
+                    "Message parsing failed: Not enough data for field value (offset: {}, length: {}, total: {})",
+                    offset,
+                    length,
+                    raw.len()
+                );
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    "Not enough data for field value".into(),
+                )
+                .at_offset(field_start)
+                .with_field_type(field_typ));
+            }
+
+            offset += length;
+        }
+
+        Ok(MessageRef { raw })
+    }
+
+    pub fn event_type(&self) -> EventType {
+        self.raw[4]
+    }
+
+    /// The complete frame, including the length prefix.
+    pub fn as_bytes(&self) -> &'a [u8] {
+        self.raw
+    }
+
+    /// Iterates over all fields in wire order.
+    pub fn fields(&self) -> FieldRefs<'a> {
+        FieldRefs {
+            raw: self.raw,
+            offset: 5,
+        }
+    }
+
+    /// Returns the value of the given field type; the last occurrence wins,
+    /// as in `Message::get_field`.
+    pub fn get_field(&self, field_type: FieldType) -> Option<&'a [u8]> {
+        self.get_fields(field_type).last()
+    }
+
+    pub fn get_fields(&self, field_type: FieldType) -> impl Iterator<Item = &'a [u8]> + use<'a> {
+        self.fields()
+            .filter(move |f| f.field_type == field_type)
+            .map(|f| f.value)
+    }
+
+    pub fn field_count(&self) -> usize {
+        self.fields().count()
+    }
+
+    /// Copies the frame into an owned `Message`.
+    pub fn to_message(&self) -> Message {
+        Message::from_ref(self, Bytes::copy_from_slice(self.raw))
+    }
+}
+
+/// Iterator over the fields of a `MessageRef`.
+#[derive(Debug, Clone)]
+pub struct FieldRefs<'a> {
+    raw: &'a [u8],
+    offset: usize,
+}
+
+impl<'a> Iterator for FieldRefs<'a> {
+    type Item = FieldRef<'a>;
+
+    fn next(&mut self) -> Option<FieldRef<'a>> {
+        // The frame was validated by MessageRef::parse, so lengths are in bounds
+        if self.offset + 5 > self.raw.len() {
+            return None;
+        }
+
+        let field_type = FieldType::from(self.raw[self.offset]);
+        let length = u32::from_be_bytes(
+            self.raw[self.offset + 1..self.offset + 5]
+                .try_into()
+                .unwrap(),
+        ) as usize;
+        let start = self.offset + 5;
+        self.offset = start + length;
+
+        Some(FieldRef {
+            field_type,
+            value: &self.raw[start..start + length],
+            offset: start,
+        })
+    }
+}
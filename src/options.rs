@@ -0,0 +1,186 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::FieldType;
+
+// Sorted field types without duplicates
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+struct FieldSet(Vec<FieldType>);
+
+impl FieldSet {
+    fn from_types(field_types: &[FieldType]) -> FieldSet {
+        let mut set = FieldSet::default();
+        for &field_type in field_types {
+            set.insert(field_type);
+        }
+        set
+    }
+
+    // Returns false if the field type was already present
+    fn insert(&mut self, field_type: FieldType) -> bool {
+        match self.0.binary_search(&field_type) {
+            Ok(_) => false,
+            Err(i) => {
+                self.0.insert(i, field_type);
+                true
+            }
+        }
+    }
+
+    fn contains(&self, field_type: FieldType) -> bool {
+        self.0.binary_search(&field_type).is_ok()
+    }
+}
+
+/// How a parser treats frames that are well-formed but suspicious: bytes
+/// after the last complete field, field types that occur more than once and
+/// field types the endpoint does not know.
+///
+/// A strict parser rejects such frames as `Malformed`. A lenient one, the
+/// default, accepts them and lists what it found in a `ParseReport`;
+/// duplicates are kept, with the last occurrence winning in `get_field`.
+#[derive(Debug, Clone, PartialEq, Eq, Hash, Default)]
+pub struct ParseOptions {
+    strict: bool,
+    // None while every field type is known
+    known: Option<FieldSet>,
+    repeated: FieldSet,
+}
+
+impl ParseOptions {
+    pub fn new() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    pub fn strict() -> ParseOptions {
+        ParseOptions {
+            strict: true,
+            ..ParseOptions::default()
+        }
+    }
+
+    pub fn lenient() -> ParseOptions {
+        ParseOptions::default()
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    /// Treats every field type not listed as unknown.
+    pub fn with_known_fields(mut self, field_types: &[FieldType]) -> ParseOptions {
+        self.known = Some(FieldSet::from_types(field_types));
+        self
+    }
+
+    /// Allows the listed field types to occur more than once.
+    pub fn with_repeated_fields(mut self, field_types: &[FieldType]) -> ParseOptions {
+        self.repeated = FieldSet::from_types(field_types);
+        self
+    }
+
+    pub(crate) fn inspector(&self) -> Inspector<'_> {
+        Inspector {
+            options: self,
+            seen: FieldSet::default(),
+            reported: FieldSet::default(),
+            report: ParseReport::default(),
+        }
+    }
+}
+
+/// What a lenient parse accepted that a strict one would have rejected.
+#[derive(Debug, Clone, PartialEq, Eq, Default)]
+pub struct ParseReport {
+    /// Bytes after the last complete field that were ignored
+    pub trailing_bytes: usize,
+    /// Field types that occurred more than once without being repeatable
+    pub duplicate_fields: Vec<FieldType>,
+    /// Field types outside the known fields
+    pub unknown_fields: Vec<FieldType>,
+}
+
+impl ParseReport {
+    /// True if nothing was skipped or tolerated.
+    pub fn is_clean(&self) -> bool {
+        self.trailing_bytes == 0
+            && self.duplicate_fields.is_empty()
+            && self.unknown_fields.is_empty()
+    }
+}
+
+/// Applies `ParseOptions` to the fields of one frame as they are read.
+pub(crate) struct Inspector<'a> {
+    options: &'a ParseOptions,
+    seen: FieldSet,
+    // field types already listed in the report
+    reported: FieldSet,
+    report: ParseReport,
+}
+
+impl Inspector<'_> {
+    /// Checks a field whose header starts at `offset`.
+    pub(crate) fn field(&mut self, field_type: FieldType, offset: usize) -> Result<(), Error> {
+        let first = self.seen.insert(field_type);
+        if self
+            .options
+            .known
+            .as_ref()
+            .is_some_and(|known| !known.contains(field_type))
+        {
+            return self.flag(
+                field_type,
+                offset,
+                format!("Unknown field type {field_type}"),
+                |report| &mut report.unknown_fields,
+            );
+        }
+        if !first && !self.options.repeated.contains(field_type) {
+            return self.flag(
+                field_type,
+                offset,
+                format!("Duplicate field type {field_type}"),
+                |report| &mut report.duplicate_fields,
+            );
+        }
+        Ok(())
+    }
+
+    fn flag(
+        &mut self,
+        field_type: FieldType,
+        offset: usize,
+        message: String,
+        list: fn(&mut ParseReport) -> &mut Vec<FieldType>,
+    ) -> Result<(), Error> {
+        if self.options.strict {
+            crate::rapid_warn!("Strict parsing failed: {}", message);
+            return Err(Error::new(ErrorCode::Malformed, message)
+                .at_offset(offset)
+                .with_field_type(field_type));
+        }
+        if self.reported.insert(field_type) {
+            list(&mut self.report).push(field_type);
+        }
+        Ok(())
+    }
+
+    /// Finishes a frame of `len` bytes whose last complete field ends at `end`.
+    pub(crate) fn finish(mut self, end: usize, len: usize) -> Result<ParseReport, Error> {
+        let trailing = len - end;
+        if trailing > 0 && self.options.strict {
+            crate::rapid_warn!(
+                "Strict parsing failed: {} trailing bytes after the last field",
+                trailing
+            );
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                format!("{trailing} trailing bytes after the last field"),
+            )
+            .at_offset(end));
+        }
+        self.report.trailing_bytes = trailing;
+        if !self.report.is_clean() {
+            crate::rapid_debug!("Lenient parsing tolerated {:?}", self.report);
+        }
+        Ok(self.report)
+    }
+}
@@ -1,5 +1,5 @@
-/// Simplified logging macros that directly use the standard log crate.
-/// The standard log macros already check if logging is enabled at the specified level.
+//! Simplified logging macros that directly use the standard log crate.
+//! The standard log macros already check if logging is enabled at the specified level.
 
 #[macro_export]
 macro_rules! rapid_debug {
@@ -0,0 +1,296 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::FieldType;
+use crate::limits::ParseLimits;
+use crate::message::{EventType, Message};
+use crate::message_ref::MessageRef;
+use crate::schema::{Presence, Schema, ValueType};
+use std::collections::HashMap;
+use std::fmt;
+use std::path::Path;
+
+/// A single way in which a message does not match its schema.
+#[derive(Debug, Clone, PartialEq)]
+pub enum ValidationError {
+    /// The event type is not declared in any registered schema
+    UnknownEvent(EventType),
+    /// A required field is absent
+    MissingField(FieldType),
+    /// A required or optional field occurs more than once
+    DuplicateField(FieldType),
+    /// A field the event does not list (strict mode only)
+    UnknownField(FieldType),
+    /// A value with the wrong width or encoding for its declared type
+    InvalidValue {
+        field_type: FieldType,
+        reason: String,
+    },
+}
+
+impl ValidationError {
+    pub fn code(&self) -> ErrorCode {
+        match self {
+            ValidationError::UnknownEvent(_) => ErrorCode::InvalidEventType,
+            _ => ErrorCode::Malformed,
+        }
+    }
+
+    pub fn field_type(&self) -> Option<FieldType> {
+        match self {
+            ValidationError::UnknownEvent(_) => None,
+            ValidationError::MissingField(ft)
+            | ValidationError::DuplicateField(ft)
+            | ValidationError::UnknownField(ft)
+            | ValidationError::InvalidValue { field_type: ft, .. } => Some(*ft),
+        }
+    }
+}
+
+impl fmt::Display for ValidationError {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            ValidationError::UnknownEvent(et) => write!(f, "Unknown event type {et}"),
+            ValidationError::MissingField(ft) => write!(f, "Missing required field type {ft}"),
+            ValidationError::DuplicateField(ft) => {
+                write!(f, "Field type {ft} may occur only once")
+            }
+            ValidationError::UnknownField(ft) => write!(f, "Unexpected field type {ft}"),
+            ValidationError::InvalidValue { field_type, reason } => {
+                write!(f, "Invalid value for field type {field_type}: {reason}")
+            }
+        }
+    }
+}
+
+impl std::error::Error for ValidationError {}
+
+impl From<ValidationError> for Error {
+    fn from(err: ValidationError) -> Error {
+        let field_type = err.field_type();
+        let error = Error::new(err.code(), err.to_string());
+        match field_type {
+            Some(ft) => error.with_field_type(ft),
+            None => error,
+        }
+    }
+}
+
+// An event's fields resolved to their numbers and value types
+struct Rule {
+    field_type: FieldType,
+    presence: Presence,
+    value_type: ValueType,
+}
+
+/// Validates messages against one or more loaded schemas.
+///
+/// Fields that an event does not list are ignored unless strict mode is on.
+#[derive(Default)]
+pub struct SchemaRegistry {
+    schema: Schema,
+    rules: HashMap<EventType, Vec<Rule>>,
+    strict: bool,
+}
+
+impl SchemaRegistry {
+    pub fn new() -> SchemaRegistry {
+        SchemaRegistry::default()
+    }
+
+    /// Adds the events and fields of `schema`.
+    ///
+    /// Declarations identical to already registered ones are shared; any
+    /// other clash of names or numbers is an error and leaves the registry
+    /// unchanged.
+    pub fn register(&mut self, schema: &Schema) -> Result<(), Error> {
+        let mut merged = self.schema.clone();
+        merged.merge(schema);
+        merged.check()?;
+
+        self.rules = merged
+            .events()
+            .iter()
+            .map(|event| {
+                let rules = event
+                    .fields
+                    .iter()
+                    .map(|used| {
+                        // check() guarantees that every listed field exists
+                        let field = merged.field(&used.name).unwrap();
+                        Rule {
+                            field_type: field.field_type,
+                            presence: used.presence,
+                            value_type: field.value_type.clone(),
+                        }
+                    })
+                    .collect();
+                (event.event_type, rules)
+            })
+            .collect();
+        self.schema = merged;
+        Ok(())
+    }
+
+    pub fn with_schema(mut self, schema: &Schema) -> Result<SchemaRegistry, Error> {
+        self.register(schema)?;
+        Ok(self)
+    }
+
+    /// Reads a schema file and registers it.
+    pub fn load(&mut self, path: impl AsRef<Path>) -> Result<(), Error> {
+        self.register(&Schema::from_file(path)?)
+    }
+
+    /// All registered declarations.
+    pub fn schema(&self) -> &Schema {
+        &self.schema
+    }
+
+    pub fn is_strict(&self) -> bool {
+        self.strict
+    }
+
+    pub fn set_strict(&mut self, strict: bool) {
+        self.strict = strict;
+    }
+
+    pub fn with_strict(mut self, strict: bool) -> SchemaRegistry {
+        self.strict = strict;
+        self
+    }
+
+    /// Checks `msg` against the schema of its event type and returns every
+    /// violation found. Nested messages are parsed within `msg`'s `ParseLimits`.
+    pub fn validate(&self, msg: &Message) -> Result<(), Vec<ValidationError>> {
+        let mut errors = Vec::new();
+        self.validate_fields(
+            msg.event_type,
+            msg.fields().map(|f| (*f.field_type(), f.value())),
+            msg.depth(),
+            msg.limits(),
+            &mut errors,
+        );
+        if errors.is_empty() {
+            Ok(())
+        } else {
+            crate::rapid_debug!(
+                "Message with event type {} failed validation: {} errors",
+                msg.event_type,
+                errors.len()
+            );
+            Err(errors)
+        }
+    }
+
+    fn validate_fields<'a>(
+        &self,
+        event_type: EventType,
+        fields: impl Iterator<Item = (FieldType, &'a [u8])>,
+        depth: u8,
+        limits: &ParseLimits,
+        errors: &mut Vec<ValidationError>,
+    ) {
+        let Some(rules) = self.rules.get(&event_type) else {
+            errors.push(ValidationError::UnknownEvent(event_type));
+            return;
+        };
+
+        let mut counts = vec![0u32; rules.len()];
+        for (field_type, value) in fields {
+            let Some(i) = rules.iter().position(|r| r.field_type == field_type) else {
+                if self.strict {
+                    errors.push(ValidationError::UnknownField(field_type));
+                }
+                continue;
+            };
+
+            let rule = &rules[i];
+            counts[i] += 1;
+            if rule.presence != Presence::Repeated && counts[i] == 2 {
+                errors.push(ValidationError::DuplicateField(field_type));
+            }
+            if let Err(reason) = self.check_value(&rule.value_type, value, depth, limits) {
+                errors.push(ValidationError::InvalidValue { field_type, reason });
+            }
+        }
+
+        for (rule, count) in rules.iter().zip(counts) {
+            if rule.presence == Presence::Required && count == 0 {
+                errors.push(ValidationError::MissingField(rule.field_type));
+            }
+        }
+    }
+
+    fn check_value(
+        &self,
+        value_type: &ValueType,
+        value: &[u8],
+        depth: u8,
+        limits: &ParseLimits,
+    ) -> Result<(), String> {
+        let width = match value_type {
+            ValueType::U8 | ValueType::Bool => 1,
+            ValueType::U16 => 2,
+            ValueType::U32 => 4,
+            ValueType::U64 | ValueType::I64 | ValueType::F64 => 8,
+            ValueType::String => {
+                return std::str::from_utf8(value)
+                    .map(|_| ())
+                    .map_err(|e| format!("invalid UTF-8: {e}"));
+            }
+            ValueType::Bytes => return Ok(()),
+            ValueType::Message | ValueType::Event(_) => {
+                return self.check_nested(value_type, value, depth, limits);
+            }
+        };
+
+        if value.len() != width {
+            return Err(format!(
+                "expected {width}-byte {value_type}, got {} bytes",
+                value.len()
+            ));
+        }
+        if *value_type == ValueType::Bool && value[0] > 1 {
+            return Err(format!("invalid boolean value {}", value[0]));
+        }
+        Ok(())
+    }
+
+    fn check_nested(
+        &self,
+        value_type: &ValueType,
+        value: &[u8],
+        depth: u8,
+        limits: &ParseLimits,
+    ) -> Result<(), String> {
+        let max_depth = limits.max_nesting_depth();
+        if depth >= max_depth {
+            return Err(format!("maximum nesting depth of {max_depth} exceeded"));
+        }
+        let view = MessageRef::parse_with_limits(value, limits).map_err(|e| e.to_string())?;
+        let ValueType::Event(name) = value_type else {
+            return Ok(());
+        };
+
+        // check() guarantees that the referenced event exists
+        let expected = self.schema.event(name).unwrap().event_type;
+        if view.event_type() != expected {
+            return Err(format!(
+                "expected nested {name} message, got event type {}",
+                view.event_type()
+            ));
+        }
+
+        let mut nested = Vec::new();
+        self.validate_fields(
+            expected,
+            view.fields().map(|f| (f.field_type(), f.value())),
+            depth + 1,
+            limits,
+            &mut nested,
+        );
+        match nested.first() {
+            None => Ok(()),
+            Some(first) => Err(format!("nested {name} message: {first}")),
+        }
+    }
+}
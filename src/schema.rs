@@ -0,0 +1,556 @@
+//! Schema files describing the events and fields of a protocol.
+//!
+//! ```text
+//! // Shared between all services
+//! field key = 0x01: string;
+//! field value = 0x02: bytes;
+//! field ttl = 0x03: u32;
+//! field group = 0x05: string;
+//!
+//! event set = 0x10 {
+//!     required key;
+//!     required value;
+//!     optional ttl;
+//!     repeated group;
+//! }
+//! ```
+//!
+//! Field types are `u8`, `u16`, `u32`, `u64`, `i64`, `f64`, `bool`, `string`,
+//! `bytes`, `message` or the name of an event, which nests that event's message.
+
+use crate::error::{EVT_ERROR, Error, ErrorCode};
+use crate::field::FieldType;
+use crate::message::EventType;
+use std::fmt;
+use std::path::Path;
+use std::str::FromStr;
+
+/// The value encoding of a field.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ValueType {
+    U8,
+    U16,
+    U32,
+    U64,
+    I64,
+    F64,
+    Bool,
+    String,
+    Bytes,
+    /// A nested message of any event type
+    Message,
+    /// A nested message of the named event
+    Event(String),
+}
+
+impl ValueType {
+    fn from_name(name: &str) -> ValueType {
+        match name {
+            "u8" => ValueType::U8,
+            "u16" => ValueType::U16,
+            "u32" => ValueType::U32,
+            "u64" => ValueType::U64,
+            "i64" => ValueType::I64,
+            "f64" => ValueType::F64,
+            "bool" => ValueType::Bool,
+            "string" => ValueType::String,
+            "bytes" => ValueType::Bytes,
+            "message" => ValueType::Message,
+            event => ValueType::Event(event.to_string()),
+        }
+    }
+}
+
+impl fmt::Display for ValueType {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        let name = match self {
+            ValueType::U8 => "u8",
+            ValueType::U16 => "u16",
+            ValueType::U32 => "u32",
+            ValueType::U64 => "u64",
+            ValueType::I64 => "i64",
+            ValueType::F64 => "f64",
+            ValueType::Bool => "bool",
+            ValueType::String => "string",
+            ValueType::Bytes => "bytes",
+            ValueType::Message => "message",
+            ValueType::Event(name) => name,
+        };
+        f.write_str(name)
+    }
+}
+
+/// How often a field may occur in an event.
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Presence {
+    Required,
+    Optional,
+    Repeated,
+}
+
+/// A field declaration: `field key = 0x01: string;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct FieldSchema {
+    pub name: String,
+    pub field_type: FieldType,
+    pub value_type: ValueType,
+}
+
+/// A field used by an event: `required key;`
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventField {
+    pub name: String,
+    pub presence: Presence,
+}
+
+/// An event declaration with the fields it carries.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct EventSchema {
+    pub name: String,
+    pub event_type: EventType,
+    pub fields: Vec<EventField>,
+}
+
+impl EventSchema {
+    pub fn new(name: &str, event_type: EventType) -> EventSchema {
+        EventSchema {
+            name: name.to_string(),
+            event_type,
+            fields: Vec::new(),
+        }
+    }
+
+    pub fn with_field(mut self, name: &str, presence: Presence) -> EventSchema {
+        self.fields.push(EventField {
+            name: name.to_string(),
+            presence,
+        });
+        self
+    }
+
+    pub fn required(self, name: &str) -> EventSchema {
+        self.with_field(name, Presence::Required)
+    }
+
+    pub fn optional(self, name: &str) -> EventSchema {
+        self.with_field(name, Presence::Optional)
+    }
+
+    pub fn repeated(self, name: &str) -> EventSchema {
+        self.with_field(name, Presence::Repeated)
+    }
+}
+
+/// A parsed schema file.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct Schema {
+    fields: Vec<FieldSchema>,
+    events: Vec<EventSchema>,
+}
+
+impl Schema {
+    /// Creates an empty schema to be filled in code.
+    ///
+    /// Schemas built this way are only checked when they are registered; call
+    /// [`Schema::check`] before using them anywhere else.
+    pub fn new() -> Schema {
+        Schema::default()
+    }
+
+    pub fn with_field(
+        mut self,
+        name: &str,
+        field_type: FieldType,
+        value_type: ValueType,
+    ) -> Schema {
+        self.fields.push(FieldSchema {
+            name: name.to_string(),
+            field_type,
+            value_type,
+        });
+        self
+    }
+
+    pub fn with_event(mut self, event: EventSchema) -> Schema {
+        self.events.push(event);
+        self
+    }
+
+    /// Reads and parses a schema file.
+    pub fn from_file(path: impl AsRef<Path>) -> Result<Schema, Error> {
+        let path = path.as_ref();
+        let source = std::fs::read_to_string(path).map_err(|e| {
+            Error::new(
+                ErrorCode::ReadFailed,
+                format!("Failed to read schema {}: {e}", path.display()),
+            )
+        })?;
+        Schema::parse(&source)
+    }
+
+    /// Parses and checks a schema file.
+    pub fn parse(source: &str) -> Result<Schema, Error> {
+        let mut parser = Parser {
+            tokens: tokenize(source)?,
+            pos: 0,
+        };
+        let mut schema = Schema::default();
+
+        while let Some(token) = parser.peek() {
+            let line = token.line;
+            match parser.ident()?.as_str() {
+                "field" => schema.fields.push(parser.field()?),
+                "event" => schema.events.push(parser.event()?),
+                other => {
+                    return Err(syntax_error(
+                        line,
+                        format!("expected `field` or `event`, found `{other}`"),
+                    ));
+                }
+            }
+        }
+
+        schema.check()?;
+        Ok(schema)
+    }
+
+    pub fn fields(&self) -> &[FieldSchema] {
+        &self.fields
+    }
+
+    pub fn events(&self) -> &[EventSchema] {
+        &self.events
+    }
+
+    pub fn field(&self, name: &str) -> Option<&FieldSchema> {
+        self.fields.iter().find(|f| f.name == name)
+    }
+
+    pub fn event(&self, name: &str) -> Option<&EventSchema> {
+        self.events.iter().find(|e| e.name == name)
+    }
+
+    pub fn field_by_type(&self, field_type: FieldType) -> Option<&FieldSchema> {
+        self.fields.iter().find(|f| f.field_type == field_type)
+    }
+
+    pub fn event_by_type(&self, event_type: EventType) -> Option<&EventSchema> {
+        self.events.iter().find(|e| e.event_type == event_type)
+    }
+
+    // Adds the declarations of `other`; identical declarations are shared
+    pub(crate) fn merge(&mut self, other: &Schema) {
+        for field in &other.fields {
+            if !self.fields.contains(field) {
+                self.fields.push(field.clone());
+            }
+        }
+        for event in &other.events {
+            if !self.events.contains(event) {
+                self.events.push(event.clone());
+            }
+        }
+    }
+
+    /// Checks that names and numbers are unique, every reference resolves and no
+    /// event requires itself.
+    pub fn check(&self) -> Result<(), Error> {
+        for (i, field) in self.fields.iter().enumerate() {
+            if let Some(other) = self.fields[..i]
+                .iter()
+                .find(|f| f.name == field.name || f.field_type == field.field_type)
+            {
+                return Err(schema_error(format!(
+                    "field `{}` ({:#04x}) conflicts with field `{}` ({:#04x})",
+                    field.name, field.field_type, other.name, other.field_type
+                )));
+            }
+            if let ValueType::Event(event) = &field.value_type
+                && self.event(event).is_none()
+            {
+                return Err(schema_error(format!(
+                    "field `{}` has unknown type `{event}`",
+                    field.name
+                )));
+            }
+        }
+
+        for (i, event) in self.events.iter().enumerate() {
+            if event.event_type == EVT_ERROR {
+                return Err(schema_error(format!(
+                    "event `{}` uses the reserved error event type {EVT_ERROR:#04x}",
+                    event.name
+                )));
+            }
+            if let Some(other) = self.events[..i]
+                .iter()
+                .find(|e| e.name == event.name || e.event_type == event.event_type)
+            {
+                return Err(schema_error(format!(
+                    "event `{}` ({:#04x}) conflicts with event `{}` ({:#04x})",
+                    event.name, event.event_type, other.name, other.event_type
+                )));
+            }
+            for (j, used) in event.fields.iter().enumerate() {
+                if self.field(&used.name).is_none() {
+                    return Err(schema_error(format!(
+                        "event `{}` uses unknown field `{}`",
+                        event.name, used.name
+                    )));
+                }
+                if event.fields[..j].iter().any(|f| f.name == used.name) {
+                    return Err(schema_error(format!(
+                        "event `{}` lists field `{}` more than once",
+                        event.name, used.name
+                    )));
+                }
+            }
+        }
+
+        // A message that requires itself could never be complete
+        for event in &self.events {
+            if self.requires(event, &event.name, &mut Vec::new()) {
+                return Err(schema_error(format!(
+                    "event `{}` requires itself; make one of the fields on the cycle optional or repeated",
+                    event.name
+                )));
+            }
+        }
+        Ok(())
+    }
+
+    // Whether `event` reaches `target` through required fields only
+    fn requires<'a>(
+        &'a self,
+        event: &'a EventSchema,
+        target: &str,
+        seen: &mut Vec<&'a str>,
+    ) -> bool {
+        for used in &event.fields {
+            if used.presence != Presence::Required {
+                continue;
+            }
+            let Some(ValueType::Event(name)) = self.field(&used.name).map(|f| &f.value_type) else {
+                continue;
+            };
+            if name == target {
+                return true;
+            }
+            if seen.contains(&name.as_str()) {
+                continue;
+            }
+            seen.push(name);
+            if let Some(next) = self.event(name)
+                && self.requires(next, target, seen)
+            {
+                return true;
+            }
+        }
+        false
+    }
+}
+
+impl FromStr for Schema {
+    type Err = Error;
+
+    fn from_str(source: &str) -> Result<Schema, Error> {
+        Schema::parse(source)
+    }
+}
+
+fn schema_error(message: String) -> Error {
+    Error::new(ErrorCode::Malformed, format!("Invalid schema: {message}"))
+}
+
+fn syntax_error(line: usize, message: String) -> Error {
+    schema_error(format!("line {line}: {message}"))
+}
+
+#[derive(Debug, PartialEq)]
+enum Kind {
+    Ident(String),
+    Number(u64),
+    Punct(char),
+}
+
+impl fmt::Display for Kind {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        match self {
+            Kind::Ident(name) => write!(f, "`{name}`"),
+            Kind::Number(n) => write!(f, "`{n}`"),
+            Kind::Punct(c) => write!(f, "`{c}`"),
+        }
+    }
+}
+
+struct Token {
+    kind: Kind,
+    line: usize,
+}
+
+fn tokenize(source: &str) -> Result<Vec<Token>, Error> {
+    let mut tokens = Vec::new();
+
+    for (index, text) in source.lines().enumerate() {
+        let line = index + 1;
+        let text = text.split("//").next().unwrap_or_default();
+        let mut chars = text.char_indices().peekable();
+
+        while let Some(&(start, c)) = chars.peek() {
+            if c.is_whitespace() {
+                chars.next();
+            } else if c.is_ascii_alphanumeric() || c == '_' {
+                let mut end = start;
+                while let Some(&(i, c)) = chars.peek() {
+                    if !(c.is_ascii_alphanumeric() || c == '_') {
+                        break;
+                    }
+                    end = i + c.len_utf8();
+                    chars.next();
+                }
+                let word = &text[start..end];
+                let kind =
+                    if c.is_ascii_digit() {
+                        Kind::Number(parse_number(word).ok_or_else(|| {
+                            syntax_error(line, format!("invalid number `{word}`"))
+                        })?)
+                    } else {
+                        Kind::Ident(word.to_string())
+                    };
+                tokens.push(Token { kind, line });
+            } else if "={};:".contains(c) {
+                tokens.push(Token {
+                    kind: Kind::Punct(c),
+                    line,
+                });
+                chars.next();
+            } else {
+                return Err(syntax_error(line, format!("unexpected character `{c}`")));
+            }
+        }
+    }
+    Ok(tokens)
+}
+
+fn parse_number(word: &str) -> Option<u64> {
+    match word.strip_prefix("0x").or_else(|| word.strip_prefix("0X")) {
+        Some(hex) => u64::from_str_radix(hex, 16).ok(),
+        None => word.parse().ok(),
+    }
+}
+
+struct Parser {
+    tokens: Vec<Token>,
+    pos: usize,
+}
+
+impl Parser {
+    fn peek(&self) -> Option<&Token> {
+        self.tokens.get(self.pos)
+    }
+
+    fn line(&self) -> usize {
+        match self.peek().or(self.tokens.last()) {
+            Some(token) => token.line,
+            None => 1,
+        }
+    }
+
+    fn next(&mut self, expected: &str) -> Result<&Kind, Error> {
+        match self.tokens.get(self.pos) {
+            Some(token) => {
+                self.pos += 1;
+                Ok(&token.kind)
+            }
+            None => Err(syntax_error(
+                self.line(),
+                format!("expected {expected}, found end of file"),
+            )),
+        }
+    }
+
+    fn ident(&mut self) -> Result<String, Error> {
+        let line = self.line();
+        match self.next("a name")? {
+            Kind::Ident(name) => Ok(name.clone()),
+            other => Err(syntax_error(
+                line,
+                format!("expected a name, found {other}"),
+            )),
+        }
+    }
+
+    fn number<T: TryFrom<u64>>(&mut self) -> Result<T, Error> {
+        let line = self.line();
+        match self.next("a number")? {
+            Kind::Number(n) => T::try_from(*n).map_err(|_| {
+                let bits = 8 * std::mem::size_of::<T>();
+                syntax_error(line, format!("number {n} does not fit in {bits} bits"))
+            }),
+            other => Err(syntax_error(
+                line,
+                format!("expected a number, found {other}"),
+            )),
+        }
+    }
+
+    fn punct(&mut self, expected: char) -> Result<(), Error> {
+        let line = self.line();
+        match self.next(&format!("`{expected}`"))? {
+            Kind::Punct(c) if *c == expected => Ok(()),
+            other => Err(syntax_error(
+                line,
+                format!("expected `{expected}`, found {other}"),
+            )),
+        }
+    }
+
+    // field <name> = <number>: <type>;
+    fn field(&mut self) -> Result<FieldSchema, Error> {
+        let name = self.ident()?;
+        self.punct('=')?;
+        let field_type = self.number()?;
+        self.punct(':')?;
+        let value_type = ValueType::from_name(&self.ident()?);
+        self.punct(';')?;
+        Ok(FieldSchema {
+            name,
+            field_type,
+            value_type,
+        })
+    }
+
+    // event <name> = <number> { <presence> <field>; ... }
+    fn event(&mut self) -> Result<EventSchema, Error> {
+        let name = self.ident()?;
+        self.punct('=')?;
+        let event_type = self.number()?;
+        self.punct('{')?;
+
+        let mut fields = Vec::new();
+        while self.peek().map(|t| &t.kind) != Some(&Kind::Punct('}')) {
+            let line = self.line();
+            let presence = match self.ident()?.as_str() {
+                "required" => Presence::Required,
+                "optional" => Presence::Optional,
+                "repeated" => Presence::Repeated,
+                other => {
+                    return Err(syntax_error(
+                        line,
+                        format!("expected `required`, `optional` or `repeated`, found `{other}`"),
+                    ));
+                }
+            };
+            let name = self.ident()?;
+            self.punct(';')?;
+            fields.push(EventField { name, presence });
+        }
+        self.punct('}')?;
+
+        Ok(EventSchema {
+            name,
+            event_type,
+            fields,
+        })
+    }
+}
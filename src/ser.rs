@@ -0,0 +1,449 @@
+use crate::error::{Error, ErrorCode};
+use crate::field::FieldType;
+use crate::message::{EventType, Message};
+use bytes::Bytes;
+use serde::Serialize;
+use serde::ser::{self, Impossible};
+use std::fmt::Display;
+
+/// Serializes a struct into a `Message` with the given event type.
+///
+/// Each struct field becomes one TLV field. A field named with a number
+/// (`#[serde(rename = "3")]`) uses that field type; all other fields are
+/// numbered by declaration order starting at 1. `None` fields are omitted,
+/// sequences become repeated fields and nested structs become nested
+/// messages with event type 0. Integers and floats are big-endian, strings
+/// UTF-8; use `serde_bytes` for byte buffers, since a plain `Vec<u8>` is a
+/// sequence and would become one field per byte. Two fields that resolve to
+/// the same field type are rejected as `Malformed`.
+pub fn to_message<T: Serialize + ?Sized>(
+    event_type: EventType,
+    value: &T,
+) -> Result<Message, Error> {
+    value.serialize(MessageSerializer { event_type })
+}
+
+impl ser::Error for Error {
+    fn custom<T: Display>(msg: T) -> Error {
+        Error::new(ErrorCode::Malformed, msg.to_string())
+    }
+}
+
+/// Numbers the fields of one struct in declaration order and rejects field
+/// types that two fields would share.
+#[derive(Default)]
+pub(crate) struct FieldNumbers {
+    index: usize,
+    used: Vec<FieldType>,
+}
+
+impl FieldNumbers {
+    /// Position of the next struct field.
+    pub(crate) fn index(&self) -> usize {
+        self.index
+    }
+
+    /// Resolves the field type for the next struct field, `name`.
+    pub(crate) fn next(&mut self, name: &str) -> Result<FieldType, Error> {
+        let field_type = field_number(name, self.index)?;
+        self.index += 1;
+
+        if self.used.contains(&field_type) {
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                format!("Struct field `{name}` reuses field type {field_type}"),
+            )
+            .with_field_type(field_type));
+        }
+        self.used.push(field_type);
+        Ok(field_type)
+    }
+}
+
+fn field_number(name: &str, index: usize) -> Result<FieldType, Error> {
+    if let Ok(field_type) = name.parse::<FieldType>() {
+        return Ok(field_type);
+    }
+    FieldType::try_from(index + 1).map_err(|_| {
+        Error::new(
+            ErrorCode::Malformed,
+            format!("Struct field `{name}` has no field type: more than 65535 fields"),
+        )
+    })
+}
+
+fn unsupported(what: &str) -> Error {
+    Error::new(
+        ErrorCode::Malformed,
+        format!("{what} cannot be represented as a TLV message"),
+    )
+}
+
+// Top level: only structs map onto a message
+struct MessageSerializer {
+    event_type: EventType,
+}
+
+macro_rules! reject {
+    ($($method:ident($($ty:ty),*);)*) => {
+        $(
+            fn $method(self, $(_: $ty),*) -> Result<Message, Error> {
+                Err(unsupported("Only structs"))
+            }
+        )*
+    };
+}
+
+impl ser::Serializer for MessageSerializer {
+    type Ok = Message;
+    type Error = Error;
+    type SerializeSeq = Impossible<Message, Error>;
+    type SerializeTuple = Impossible<Message, Error>;
+    type SerializeTupleStruct = Impossible<Message, Error>;
+    type SerializeTupleVariant = Impossible<Message, Error>;
+    type SerializeMap = Impossible<Message, Error>;
+    type SerializeStruct = StructSerializer;
+    type SerializeStructVariant = Impossible<Message, Error>;
+
+    reject! {
+        serialize_bool(bool);
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+        serialize_char(char);
+        serialize_str(&str);
+        serialize_bytes(&[u8]);
+        serialize_none();
+        serialize_unit();
+        serialize_unit_struct(&'static str);
+        serialize_unit_variant(&'static str, u32, &'static str);
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, _: &T) -> Result<Message, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<Message, Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<Message, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("Only structs"))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<StructSerializer, Error> {
+        Ok(StructSerializer {
+            msg: Message::new(self.event_type),
+            numbers: FieldNumbers::default(),
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("Only structs"))
+    }
+}
+
+struct StructSerializer {
+    msg: Message,
+    numbers: FieldNumbers,
+}
+
+impl ser::SerializeStruct for StructSerializer {
+    type Ok = Message;
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        let field_type = self.numbers.next(key)?;
+        value.serialize(FieldSerializer {
+            msg: &mut self.msg,
+            field_type,
+            in_seq: false,
+        })
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+        // Skipped fields keep their declaration-order number reserved
+        self.numbers.next(key).map(|_| ())
+    }
+
+    fn end(self) -> Result<Message, Error> {
+        Ok(self.msg)
+    }
+}
+
+// Writes one struct field value, or one element of a repeated field
+struct FieldSerializer<'a> {
+    msg: &'a mut Message,
+    field_type: FieldType,
+    in_seq: bool,
+}
+
+impl FieldSerializer<'_> {
+    fn put(self, value: Bytes) -> Result<(), Error> {
+        self.msg.add_repeated_field(self.field_type, value);
+        Ok(())
+    }
+}
+
+macro_rules! serialize_be {
+    ($($method:ident($ty:ty);)*) => {
+        $(
+            fn $method(self, v: $ty) -> Result<(), Error> {
+                self.put(Bytes::copy_from_slice(&v.to_be_bytes()))
+            }
+        )*
+    };
+}
+
+impl<'a> ser::Serializer for FieldSerializer<'a> {
+    type Ok = ();
+    type Error = Error;
+    type SerializeSeq = SeqSerializer<'a>;
+    type SerializeTuple = Impossible<(), Error>;
+    type SerializeTupleStruct = Impossible<(), Error>;
+    type SerializeTupleVariant = Impossible<(), Error>;
+    type SerializeMap = Impossible<(), Error>;
+    type SerializeStruct = NestedSerializer<'a>;
+    type SerializeStructVariant = Impossible<(), Error>;
+
+    serialize_be! {
+        serialize_i8(i8);
+        serialize_i16(i16);
+        serialize_i32(i32);
+        serialize_i64(i64);
+        serialize_u8(u8);
+        serialize_u16(u16);
+        serialize_u32(u32);
+        serialize_u64(u64);
+        serialize_f32(f32);
+        serialize_f64(f64);
+    }
+
+    fn serialize_bool(self, v: bool) -> Result<(), Error> {
+        self.put(Bytes::copy_from_slice(&[v as u8]))
+    }
+
+    fn serialize_char(self, v: char) -> Result<(), Error> {
+        self.put(Bytes::copy_from_slice(
+            v.encode_utf8(&mut [0; 4]).as_bytes(),
+        ))
+    }
+
+    fn serialize_str(self, v: &str) -> Result<(), Error> {
+        self.put(Bytes::copy_from_slice(v.as_bytes()))
+    }
+
+    fn serialize_bytes(self, v: &[u8]) -> Result<(), Error> {
+        self.put(Bytes::copy_from_slice(v))
+    }
+
+    fn serialize_none(self) -> Result<(), Error> {
+        Ok(())
+    }
+
+    fn serialize_some<T: Serialize + ?Sized>(self, value: &T) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_unit(self) -> Result<(), Error> {
+        self.put(Bytes::new())
+    }
+
+    fn serialize_unit_struct(self, _: &'static str) -> Result<(), Error> {
+        self.put(Bytes::new())
+    }
+
+    // Unit enum variants are stored as their u32 index
+    fn serialize_unit_variant(
+        self,
+        _: &'static str,
+        index: u32,
+        _: &'static str,
+    ) -> Result<(), Error> {
+        self.serialize_u32(index)
+    }
+
+    fn serialize_newtype_struct<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        value.serialize(self)
+    }
+
+    fn serialize_newtype_variant<T: Serialize + ?Sized>(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: &T,
+    ) -> Result<(), Error> {
+        Err(unsupported("Enum variant with data"))
+    }
+
+    fn serialize_seq(self, _: Option<usize>) -> Result<Self::SerializeSeq, Error> {
+        if self.in_seq {
+            return Err(unsupported("Nested sequence"));
+        }
+        Ok(SeqSerializer {
+            msg: self.msg,
+            field_type: self.field_type,
+        })
+    }
+
+    fn serialize_tuple(self, _: usize) -> Result<Self::SerializeTuple, Error> {
+        Err(unsupported("Tuple"))
+    }
+
+    fn serialize_tuple_struct(
+        self,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleStruct, Error> {
+        Err(unsupported("Tuple struct"))
+    }
+
+    fn serialize_tuple_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeTupleVariant, Error> {
+        Err(unsupported("Enum variant with data"))
+    }
+
+    fn serialize_map(self, _: Option<usize>) -> Result<Self::SerializeMap, Error> {
+        Err(unsupported("Map"))
+    }
+
+    fn serialize_struct(self, _: &'static str, _: usize) -> Result<Self::SerializeStruct, Error> {
+        Ok(NestedSerializer {
+            msg: self.msg,
+            field_type: self.field_type,
+            inner: StructSerializer {
+                msg: Message::new(0),
+                numbers: FieldNumbers::default(),
+            },
+        })
+    }
+
+    fn serialize_struct_variant(
+        self,
+        _: &'static str,
+        _: u32,
+        _: &'static str,
+        _: usize,
+    ) -> Result<Self::SerializeStructVariant, Error> {
+        Err(unsupported("Enum variant with data"))
+    }
+}
+
+struct SeqSerializer<'a> {
+    msg: &'a mut Message,
+    field_type: FieldType,
+}
+
+impl ser::SerializeSeq for SeqSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_element<T: Serialize + ?Sized>(&mut self, value: &T) -> Result<(), Error> {
+        value.serialize(FieldSerializer {
+            msg: self.msg,
+            field_type: self.field_type,
+            in_seq: true,
+        })
+    }
+
+    fn end(self) -> Result<(), Error> {
+        Ok(())
+    }
+}
+
+// Collects a nested struct into its own message, then stores it as a field value
+struct NestedSerializer<'a> {
+    msg: &'a mut Message,
+    field_type: FieldType,
+    inner: StructSerializer,
+}
+
+impl ser::SerializeStruct for NestedSerializer<'_> {
+    type Ok = ();
+    type Error = Error;
+
+    fn serialize_field<T: Serialize + ?Sized>(
+        &mut self,
+        key: &'static str,
+        value: &T,
+    ) -> Result<(), Error> {
+        ser::SerializeStruct::serialize_field(&mut self.inner, key, value)
+    }
+
+    fn skip_field(&mut self, key: &'static str) -> Result<(), Error> {
+        ser::SerializeStruct::skip_field(&mut self.inner, key)
+    }
+
+    fn end(self) -> Result<(), Error> {
+        let nested = self.inner.msg.to_bytes()?;
+        self.msg.add_repeated_field(self.field_type, nested);
+        Ok(())
+    }
+}
@@ -0,0 +1,619 @@
+use crate::compress::{
+    self, Compression, DEFAULT_COMPRESSION_THRESHOLD, DEFAULT_MAX_DECOMPRESSED_SIZE,
+};
+use crate::error::{Error, ErrorCode};
+use crate::format::WireFormat;
+use crate::limits::ParseLimits;
+use crate::message::Message;
+use crate::options::{Inspector, ParseOptions};
+use bytes::{BufMut, Bytes, BytesMut};
+use std::io::IoSlice;
+
+/// Header flag: the fields section is compressed.
+pub const FLAG_COMPRESSED: u8 = 0x01;
+/// Header flag: the frame ends with a checksum.
+pub const FLAG_CHECKSUMMED: u8 = 0x02;
+/// Header flag: an 8-byte correlation id follows the event type.
+pub const FLAG_CORRELATION_ID: u8 = 0x04;
+
+/// Newest header version this crate can read and write.
+pub const LATEST_WIRE_VERSION: u8 = 1;
+
+const CORRELATION_ID_LEN: usize = 8;
+const CHECKSUM_LEN: usize = 4;
+// Algorithm and decompressed length before compressed fields
+const COMPRESSION_HEADER_LEN: usize = 5;
+
+/// Header layout shared by both ends of a connection.
+///
+/// The default is the original unversioned header, `[length][event type]`.
+/// A versioned header, `[length][version][flags][event type]`, lets the wire
+/// format evolve: receivers reject versions newer than their own with
+/// `UnsupportedVersion` and flags they do not understand with `Malformed`.
+/// The widths and byte order of lengths and field headers come from the
+/// config's `WireFormat`.
+///
+/// With checksums enabled every frame ends with a CRC32C of all bytes before
+/// it, length prefix included. A versioned header marks such frames with
+/// `FLAG_CHECKSUMMED`; the unversioned header has no flags, so both sides
+/// must enable checksums.
+///
+/// With compression enabled, fields sections of at least the threshold are
+/// compressed and marked with `FLAG_COMPRESSED`. This needs a versioned
+/// header; the unversioned header is always sent uncompressed. Receivers
+/// decompress flagged frames whatever their own compression setting, up to
+/// `max_decompressed_size` or the frame size limit, whichever is smaller.
+///
+/// Every frame read with a config is held to its `ParseLimits`; the frame
+/// size is checked as soon as the length prefix is read. Its `ParseOptions`
+/// decide whether trailing bytes, duplicate and unknown fields are rejected.
+/// Lenient parsing only logs what it tolerated.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct WireConfig {
+    // None for the unversioned header
+    version: Option<u8>,
+    format: WireFormat,
+    checksum: bool,
+    compression: Option<Compression>,
+    compression_threshold: usize,
+    max_decompressed_size: usize,
+    limits: ParseLimits,
+    options: ParseOptions,
+}
+
+impl Default for WireConfig {
+    fn default() -> Self {
+        WireConfig {
+            version: None,
+            format: WireFormat::default(),
+            checksum: false,
+            compression: None,
+            compression_threshold: DEFAULT_COMPRESSION_THRESHOLD,
+            max_decompressed_size: DEFAULT_MAX_DECOMPRESSED_SIZE,
+            limits: ParseLimits::default(),
+            options: ParseOptions::default(),
+        }
+    }
+}
+
+impl WireConfig {
+    /// The original header without version and flags.
+    pub fn legacy() -> WireConfig {
+        WireConfig::default()
+    }
+
+    /// A versioned header; `version` must be between 1 and `LATEST_WIRE_VERSION`.
+    pub fn versioned(version: u8) -> Result<WireConfig, Error> {
+        check_version(version)?;
+        Ok(WireConfig {
+            version: Some(version),
+            ..WireConfig::default()
+        })
+    }
+
+    pub fn with_format(mut self, format: WireFormat) -> WireConfig {
+        self.format = format;
+        self
+    }
+
+    pub fn format(&self) -> &WireFormat {
+        &self.format
+    }
+
+    /// Appends a checksum to every frame and rejects frames without one.
+    pub fn with_checksum(mut self, checksum: bool) -> WireConfig {
+        self.checksum = checksum;
+        self
+    }
+
+    pub fn has_checksum(&self) -> bool {
+        self.checksum
+    }
+
+    /// Compresses fields sections of at least `compression_threshold` bytes.
+    pub fn with_compression(mut self, compression: Compression) -> WireConfig {
+        self.compression = Some(compression);
+        self
+    }
+
+    pub fn compression(&self) -> Option<Compression> {
+        self.compression
+    }
+
+    pub fn with_compression_threshold(mut self, threshold: usize) -> WireConfig {
+        self.compression_threshold = threshold;
+        self
+    }
+
+    pub fn compression_threshold(&self) -> usize {
+        self.compression_threshold
+    }
+
+    /// Rejects compressed frames that claim to decompress to more than
+    /// `size` bytes with `ValueTooLarge`, before decompressing them. The
+    /// frame size limit applies as well if it is smaller.
+    pub fn with_max_decompressed_size(mut self, size: usize) -> WireConfig {
+        self.max_decompressed_size = size;
+        self
+    }
+
+    pub fn max_decompressed_size(&self) -> usize {
+        self.max_decompressed_size
+    }
+
+    pub fn with_limits(mut self, limits: ParseLimits) -> WireConfig {
+        self.limits = limits;
+        self
+    }
+
+    pub fn limits(&self) -> &ParseLimits {
+        &self.limits
+    }
+
+    pub fn with_parse_options(mut self, options: ParseOptions) -> WireConfig {
+        self.options = options;
+        self
+    }
+
+    pub fn parse_options(&self) -> &ParseOptions {
+        &self.options
+    }
+
+    pub fn is_versioned(&self) -> bool {
+        self.version.is_some()
+    }
+
+    /// The header version written by this side, if versioned.
+    pub fn version(&self) -> Option<u8> {
+        self.version
+    }
+
+    /// Picks the configuration both sides can speak: the lower of the two
+    /// versions, or the legacy header if either side does not use versions.
+    ///
+    /// Both sides must use the same `WireFormat`; it cannot be negotiated
+    /// because it is needed to read the negotiation itself. Checksums are
+    /// used if either side asks for them, unless the result is the legacy
+    /// header, where both sides must agree. Compression settings stay this
+    /// side's own.
+    pub fn negotiate(&self, peer: &WireConfig) -> Result<WireConfig, Error> {
+        if self.format != peer.format {
+            return Err(Error::new(
+                ErrorCode::ConfigInvalid,
+                "Peers use different wire formats".into(),
+            ));
+        }
+        let version = match (self.version, peer.version) {
+            (Some(ours), Some(theirs)) => Some(ours.min(theirs)),
+            _ => None,
+        };
+        if version.is_none() && self.checksum != peer.checksum {
+            return Err(Error::new(
+                ErrorCode::ConfigInvalid,
+                "Peers disagree on checksums and have no header flag to signal them".into(),
+            ));
+        }
+        Ok(WireConfig {
+            version,
+            checksum: self.checksum || peer.checksum,
+            ..self.clone()
+        })
+    }
+
+    /// True for the layout `Message::parse` and `Message::encode` use.
+    pub(crate) fn is_legacy(&self) -> bool {
+        let layout = WireConfig {
+            limits: ParseLimits::default(),
+            options: ParseOptions::default(),
+            ..*self
+        };
+        layout == WireConfig::default()
+    }
+
+    // Flags this configuration knows how to handle
+    fn supported_flags(&self) -> u8 {
+        let flags = FLAG_CHECKSUMMED | FLAG_CORRELATION_ID;
+        if Compression::SUPPORTED {
+            flags | FLAG_COMPRESSED
+        } else {
+            flags
+        }
+    }
+
+    // Version, flags and event type: the header after the length prefix
+    fn fixed_header_len(&self) -> usize {
+        if self.version.is_some() { 3 } else { 1 }
+    }
+
+    // Bytes after the fields that every frame carries
+    fn trailer_len(&self) -> usize {
+        if self.checksum { CHECKSUM_LEN } else { 0 }
+    }
+
+    /// Smallest possible frame, i.e. a header without optional parts.
+    pub(crate) fn header_len(&self) -> usize {
+        self.format.min_prefix_len() + self.fixed_header_len() + self.trailer_len()
+    }
+
+    /// Reads the declared frame length from the start of `buf`.
+    ///
+    /// Returns `Ok(None)` while too few bytes are buffered to tell.
+    pub(crate) fn frame_len(&self, buf: &[u8]) -> Result<Option<usize>, Error> {
+        Ok(self.read_prefix(buf)?.map(|(frame_len, _)| frame_len))
+    }
+
+    // Total frame length and size of the length prefix
+    fn read_prefix(&self, buf: &[u8]) -> Result<Option<(usize, usize)>, Error> {
+        let Some((frame_len, prefix_len)) =
+            self.format.read_prefix(buf).map_err(|e| e.at_offset(0))?
+        else {
+            return Ok(None);
+        };
+        self.limits.check_frame_size(frame_len)?;
+        if frame_len < prefix_len + self.fixed_header_len() + self.trailer_len() {
+            crate::rapid_warn!(
+                "Frame decoding failed: Declared length ({}) is smaller than the TLV header",
+                frame_len
+            );
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                "Declared length is smaller than the TLV header".into(),
+            )
+            .at_offset(0));
+        }
+        Ok(Some((frame_len, prefix_len)))
+    }
+
+    /// Parses one complete frame.
+    pub(crate) fn parse(&self, raw: Bytes) -> Result<Message, Error> {
+        if self.is_legacy() {
+            if self.options == ParseOptions::default() {
+                return Message::parse_with_limits(raw, &self.limits);
+            }
+            return Message::parse_inspected(raw, &self.limits, &self.options).map(|(msg, _)| msg);
+        }
+
+        crate::rapid_debug!("Parsing message from {} bytes with {:?}", raw.len(), self);
+        let prefix = match self.read_prefix(&raw)? {
+            Some((frame_len, prefix_len)) if frame_len == raw.len() => Some(prefix_len),
+            Some(_) => None,
+            None if raw.len() < self.header_len() => {
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    "Not enough data for TLV header".into(),
+                )
+                .at_offset(0));
+            }
+            None => None,
+        };
+        let Some(mut offset) = prefix else {
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                "Malformed tlv field: Declared length doesn't match actual length".into(),
+            )
+            .at_offset(0));
+        };
+
+        // Without a header only the config tells whether a checksum follows
+        let (flags, checksummed) = if self.version.is_some() {
+            let flags = raw[offset + 1];
+            (flags, flags & FLAG_CHECKSUMMED != 0)
+        } else {
+            (0, self.checksum)
+        };
+        // A corrupted header is reported as a checksum error, so the
+        // checksum is verified before anything else in the frame is trusted
+        let mut end = raw.len();
+        if checksummed {
+            end = self.verify_checksum(&raw, offset)?;
+        }
+
+        if self.version.is_some() {
+            let version = raw[offset];
+            if let Err(e) = self.accept_version(version) {
+                return Err(e.at_offset(offset));
+            }
+            if self.checksum && flags & FLAG_CHECKSUMMED == 0 {
+                return Err(
+                    Error::new(ErrorCode::Malformed, "Frame has no checksum".into())
+                        .at_offset(offset + 1),
+                );
+            }
+            let unsupported = flags & !self.supported_flags();
+            if unsupported != 0 {
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    format!("Unsupported header flags {unsupported:#04x}"),
+                )
+                .at_offset(offset + 1));
+            }
+            offset += 2;
+        }
+
+        let raw = raw.slice(..end);
+        let mut msg = Message::new(raw[offset]);
+        msg.set_limits(self.limits);
+        offset += 1;
+        if flags & FLAG_CORRELATION_ID != 0 {
+            let Some(id) = raw.get(offset..offset + CORRELATION_ID_LEN) else {
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    "Not enough data for correlation id".into(),
+                )
+                .at_offset(offset));
+            };
+            msg.set_correlation_id(Some(self.format.read_u64(id)));
+            offset += CORRELATION_ID_LEN;
+        }
+
+        let mut inspector = self.options.inspector();
+        if flags & FLAG_COMPRESSED != 0 {
+            let fields = self.decompress_fields(&raw, offset)?;
+            let end = self.parse_fields(&mut msg, &fields, 0, &mut inspector)?;
+            inspector.finish(end, fields.len())?;
+        } else {
+            let end = self.parse_fields(&mut msg, &raw, offset, &mut inspector)?;
+            inspector.finish(end, raw.len())?;
+        }
+        crate::rapid_debug!(
+            "Message parsed: event_type {}, {} fields",
+            msg.event_type,
+            msg.field_count()
+        );
+        Ok(msg)
+    }
+
+    // Checks the trailing checksum and returns where the trailer starts
+    fn verify_checksum(&self, raw: &[u8], header_start: usize) -> Result<usize, Error> {
+        let trailer = raw.len().saturating_sub(CHECKSUM_LEN);
+        if trailer < header_start + self.fixed_header_len() {
+            return Err(
+                Error::new(ErrorCode::Malformed, "Not enough data for checksum".into())
+                    .at_offset(header_start),
+            );
+        }
+        let expected = self.format.read_u32(&raw[trailer..]);
+        let actual = crc32c::crc32c(&raw[..trailer]);
+        if expected != actual {
+            crate::rapid_warn!(
+                "Frame decoding failed: Checksum {:#010x} does not match computed {:#010x}",
+                expected,
+                actual
+            );
+            return Err(Error::new(
+                ErrorCode::ChecksumMismatch,
+                format!("Checksum mismatch: frame has {expected:#010x}, computed {actual:#010x}"),
+            )
+            .at_offset(trailer));
+        }
+        Ok(trailer)
+    }
+
+    // Decompresses the fields section starting at `start`
+    fn decompress_fields(&self, raw: &[u8], start: usize) -> Result<Bytes, Error> {
+        let Some(header) = raw.get(start..start + COMPRESSION_HEADER_LEN) else {
+            return Err(Error::new(
+                ErrorCode::Malformed,
+                "Not enough data for compression header".into(),
+            )
+            .at_offset(start));
+        };
+        let len = self.format.read_u32(&header[1..]) as usize;
+        let limit = self.max_decompressed_size.min(self.limits.max_frame_size());
+        if len > limit {
+            crate::rapid_warn!(
+                "Frame decoding failed: Fields decompress to {} bytes, limit is {}",
+                len,
+                limit
+            );
+            return Err(Error::new(
+                ErrorCode::ValueTooLarge,
+                format!("Fields decompress to {len} bytes, more than the limit of {limit}"),
+            )
+            .at_offset(start));
+        }
+        compress::decompress(header[0], &raw[start + COMPRESSION_HEADER_LEN..], len)
+            .map(Bytes::from)
+            .map_err(|e| e.at_offset(start))
+    }
+
+    fn accept_version(&self, version: u8) -> Result<(), Error> {
+        check_version(version)?;
+        match self.version {
+            Some(ours) if version > ours => Err(Error::new(
+                ErrorCode::UnsupportedVersion,
+                format!("Wire version {version} is newer than the configured version {ours}"),
+            )),
+            _ => Ok(()),
+        }
+    }
+
+    // Reads fields from `start` to the end of `raw` and returns where the
+    // last complete field ends; a trailing partial header is left to the
+    // inspector like in `MessageRef::parse`
+    fn parse_fields(
+        &self,
+        msg: &mut Message,
+        raw: &Bytes,
+        start: usize,
+        inspector: &mut Inspector<'_>,
+    ) -> Result<usize, Error> {
+        let mut offset = start;
+        let mut count = 0;
+        while let Some((field_type, len, header_len)) = self
+            .format
+            .read_field_header(&raw[offset..])
+            .map_err(|e| e.at_offset(offset))?
+        {
+            count += 1;
+            self.limits.check_field(field_type, len, count, offset)?;
+            inspector.field(field_type, offset)?;
+            let value_start = offset + header_len;
+            if len > raw.len() - value_start {
+                return Err(Error::new(
+                    ErrorCode::Malformed,
+                    "Not enough data for field value".into(),
+                )
+                .at_offset(offset)
+                .with_field_type(field_type));
+            }
+            msg.add_repeated_field(field_type, raw.slice(value_start..value_start + len));
+            offset = value_start + len;
+        }
+        Ok(offset)
+    }
+
+    /// Encodes `msg` as one complete frame.
+    pub(crate) fn encode(&self, msg: &Message) -> Result<Bytes, Error> {
+        if self.is_legacy() {
+            return msg.to_bytes();
+        }
+        Ok(self.encode_parts(msg)?.to_bytes())
+    }
+
+    /// Encodes `msg` as generated header bytes interleaved with the field
+    /// values, which stay borrowed from the message unless the fields section
+    /// is compressed.
+    pub(crate) fn encode_parts<'a>(&self, msg: &'a Message) -> Result<FrameParts<'a>, Error> {
+        let mut flags = 0;
+        let mut body_len = self.fixed_header_len() + self.trailer_len();
+        if self.version.is_some() && msg.correlation_id().is_some() {
+            flags |= FLAG_CORRELATION_ID;
+            body_len += CORRELATION_ID_LEN;
+        }
+        if self.checksum {
+            flags |= FLAG_CHECKSUMMED;
+        }
+        let fields_len = msg
+            .fields()
+            .map(|f| self.format.field_len(f.value().len()))
+            .sum::<usize>();
+        let compressed = self.compress_fields(msg, fields_len)?;
+        match &compressed {
+            Some(data) => {
+                flags |= FLAG_COMPRESSED;
+                body_len += COMPRESSION_HEADER_LEN + data.len();
+            }
+            None => body_len += fields_len,
+        }
+
+        let mut frame = FrameParts::default();
+        let head = &mut frame.head;
+        self.format.put_prefix(head, body_len)?;
+        if let Some(version) = self.version {
+            head.put_u8(version);
+            head.put_u8(flags);
+        }
+        head.put_u8(msg.event_type);
+        if flags & FLAG_CORRELATION_ID != 0 {
+            self.format.put_u64(head, msg.correlation_id().unwrap());
+        }
+        if let (Some(data), Some(compression)) = (&compressed, self.compression) {
+            head.put_u8(compression.id());
+            self.format.put_u32(head, fields_len as u32);
+            head.put_slice(data);
+        } else {
+            for field in msg.fields() {
+                self.format.put_field_header(
+                    &mut frame.head,
+                    *field.field_type(),
+                    field.value().len(),
+                )?;
+                frame.push_value(field.value());
+            }
+        }
+        if self.checksum {
+            let checksum = frame.crc32c();
+            self.format.put_u32(&mut frame.head, checksum);
+        }
+        Ok(frame)
+    }
+
+    // The compressed fields section, if compression is on, the section is
+    // large enough and compressing it saves space
+    fn compress_fields(&self, msg: &Message, fields_len: usize) -> Result<Option<Vec<u8>>, Error> {
+        let Some(compression) = self.compression else {
+            return Ok(None);
+        };
+        if self.version.is_none()
+            || fields_len < self.compression_threshold
+            || fields_len > u32::MAX as usize
+        {
+            return Ok(None);
+        }
+
+        let mut fields = BytesMut::with_capacity(fields_len);
+        for field in msg.fields() {
+            field.write_with(&self.format, &mut fields)?;
+        }
+        let data = compression.compress(&fields)?;
+        if data.len() + COMPRESSION_HEADER_LEN >= fields_len {
+            crate::rapid_trace!(
+                "Sending {} bytes of fields uncompressed: {:?} saves nothing",
+                fields_len,
+                compression
+            );
+            return Ok(None);
+        }
+        Ok(Some(data))
+    }
+}
+
+fn check_version(version: u8) -> Result<(), Error> {
+    if version == 0 || version > LATEST_WIRE_VERSION {
+        crate::rapid_warn!("Unsupported wire version {}", version);
+        return Err(Error::new(
+            ErrorCode::UnsupportedVersion,
+            format!("Unsupported wire version {version}"),
+        ));
+    }
+    Ok(())
+}
+
+/// One encoded frame: generated bytes with borrowed field values spliced in.
+#[derive(Default)]
+pub(crate) struct FrameParts<'a> {
+    head: BytesMut,
+    // each value follows the head bytes written before it
+    values: Vec<(usize, &'a [u8])>,
+}
+
+impl<'a> FrameParts<'a> {
+    fn push_value(&mut self, value: &'a [u8]) {
+        self.values.push((self.head.len(), value));
+    }
+
+    /// The frame in order, ready for a vectored write.
+    pub(crate) fn slices(&self) -> Vec<IoSlice<'_>> {
+        let mut slices = Vec::with_capacity(2 * self.values.len() + 1);
+        let mut start = 0;
+        for &(end, value) in &self.values {
+            if end > start {
+                slices.push(IoSlice::new(&self.head[start..end]));
+            }
+            if !value.is_empty() {
+                slices.push(IoSlice::new(value));
+            }
+            start = end;
+        }
+        if start < self.head.len() {
+            slices.push(IoSlice::new(&self.head[start..]));
+        }
+        slices
+    }
+
+    fn crc32c(&self) -> u32 {
+        self.slices()
+            .iter()
+            .fold(0, |crc, slice| crc32c::crc32c_append(crc, slice))
+    }
+
+    fn to_bytes(&self) -> Bytes {
+        let slices = self.slices();
+        let mut frame = BytesMut::with_capacity(slices.iter().map(|s| s.len()).sum());
+        for slice in &slices {
+            frame.put_slice(slice);
+        }
+        frame.freeze()
+    }
+}
@@ -0,0 +1,151 @@
+#![cfg(feature = "auth")]
+
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    AUTH_TAG_LEN, RapidTlvErrorCode, RapidTlvFrameDecoder, RapidTlvKeyring, RapidTlvMessage,
+    RapidTlvReader, RapidTlvWireConfig, RapidTlvWriter,
+};
+
+use common::{EVT_SET, FIELD_KEY, FIELD_VALUE, set};
+
+#[test]
+fn test_authenticated_roundtrip() {
+    let keyring = RapidTlvKeyring::new()
+        .with_key(1, b"relay secret".to_vec())
+        .with_current(1);
+    let frame = set().to_bytes_authenticated(&keyring).unwrap();
+    let plain = set().to_bytes().unwrap();
+
+    // [length][event type][fields][key id][tag], the length counting everything
+    assert_eq!(frame.len(), plain.len() + 1 + AUTH_TAG_LEN);
+    assert_eq!(&frame[0..4], &(frame.len() as u32).to_be_bytes());
+    assert_eq!(frame[plain.len()], 1);
+
+    let parsed = RapidTlvMessage::parse_authenticated(frame.clone(), &keyring).unwrap();
+    assert_eq!(parsed.get_field(&FIELD_VALUE).unwrap().value(), b"value");
+    assert_eq!(parsed.to_bytes().unwrap(), plain);
+}
+
+#[test]
+fn test_tampered_frames_are_rejected() {
+    let keyring = RapidTlvKeyring::new()
+        .with_key(1, b"relay secret".to_vec())
+        .with_current(1);
+    let frame = set().to_bytes_authenticated(&keyring).unwrap();
+
+    for offset in 4..frame.len() {
+        let mut tampered = frame.to_vec();
+        tampered[offset] ^= 0x01;
+        let err =
+            RapidTlvMessage::parse_authenticated(Bytes::from(tampered), &keyring).unwrap_err();
+        assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+    }
+
+    let other = RapidTlvKeyring::new()
+        .with_key(1, b"another secret".to_vec())
+        .with_current(1);
+    let err = RapidTlvMessage::parse_authenticated(frame.clone(), &other).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+    assert_eq!(err.offset(), Some(frame.len() - AUTH_TAG_LEN));
+
+    // A plain frame has no tag to check
+    let err =
+        RapidTlvMessage::parse_authenticated(set().to_bytes().unwrap(), &keyring).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+
+    // Neither has a frame cut short, even with a fixed up length
+    for len in [3, 5, frame.len() - 1] {
+        let mut short = frame[..len].to_vec();
+        if len >= 4 {
+            short[0..4].copy_from_slice(&(len as u32).to_be_bytes());
+        }
+        assert!(RapidTlvMessage::parse_authenticated(Bytes::from(short), &keyring).is_err());
+    }
+
+    // A message without fields is still signed
+    let empty = RapidTlvMessage::new(EVT_SET)
+        .to_bytes_authenticated(&keyring)
+        .unwrap();
+    assert_eq!(empty.len(), 5 + 1 + AUTH_TAG_LEN);
+    let mut tampered = empty.to_vec();
+    tampered[4] ^= 0x01;
+    let err = RapidTlvMessage::parse_authenticated(Bytes::from(tampered), &keyring).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+}
+
+#[test]
+fn test_key_rotation() {
+    let mut sender = RapidTlvKeyring::new()
+        .with_key(1, b"old".to_vec())
+        .with_current(1);
+    let mut receiver = RapidTlvKeyring::new().with_key(1, b"old".to_vec());
+    let old_frame = set().to_bytes_authenticated(&sender).unwrap();
+
+    // The new key reaches the receivers before senders switch to it
+    receiver.add_key(2, b"new".to_vec());
+    sender.add_key(2, b"new".to_vec());
+    sender.set_current(2);
+    let new_frame = set().to_bytes_authenticated(&sender).unwrap();
+    assert!(RapidTlvMessage::parse_authenticated(old_frame.clone(), &receiver).is_ok());
+    assert!(RapidTlvMessage::parse_authenticated(new_frame.clone(), &receiver).is_ok());
+
+    assert!(receiver.remove_key(1));
+    let err = RapidTlvMessage::parse_authenticated(old_frame, &receiver).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+    assert!(RapidTlvMessage::parse_authenticated(new_frame, &receiver).is_ok());
+
+    // Keys never show up in debug output
+    assert!(!format!("{receiver:?}").contains("110"));
+
+    let err = set()
+        .to_bytes_authenticated(&RapidTlvKeyring::new())
+        .unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ConfigInvalid);
+}
+
+#[test]
+fn test_authenticated_stream() {
+    let keyring = RapidTlvKeyring::new()
+        .with_key(1, b"relay secret".to_vec())
+        .with_current(1);
+    let config = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_checksum(true);
+
+    let mut writer = RapidTlvWriter::new(Vec::new())
+        .with_config(config.clone())
+        .with_keyring(keyring.clone());
+    writer.write_message(&set().with_correlation_id(3)).unwrap();
+    writer.write_message(&set()).unwrap();
+    let stream = writer.into_inner().unwrap();
+
+    // Frames arrive in small chunks
+    let mut decoder = RapidTlvFrameDecoder::new()
+        .with_config(config.clone())
+        .with_keyring(keyring.clone());
+    let mut messages = Vec::new();
+    for chunk in stream.chunks(7) {
+        messages.extend(decoder.feed(chunk).unwrap());
+    }
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].correlation_id(), Some(3));
+    assert_eq!(messages[1].get_field(&FIELD_KEY).unwrap().value(), b"key");
+
+    let mut reader = RapidTlvReader::new(&stream[..])
+        .with_config(config.clone())
+        .with_keyring(keyring.clone());
+    assert_eq!(reader.read_message().unwrap().unwrap(), set());
+
+    // A frame signed with an unknown key is rejected by the stream decoder
+    let other = RapidTlvKeyring::new()
+        .with_key(2, b"other secret".to_vec())
+        .with_current(2);
+    let frame = set().to_bytes_authenticated_with(&other, &config).unwrap();
+    let mut decoder = RapidTlvFrameDecoder::new()
+        .with_config(config)
+        .with_keyring(keyring);
+    let err = decoder.feed(&frame).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+}
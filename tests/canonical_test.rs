@@ -0,0 +1,76 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvFieldOrder, RapidTlvMessage, RapidTlvWireConfig};
+use std::collections::HashSet;
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_TAG: u16 = 0x03;
+
+#[test]
+fn test_canonical_encoding_ignores_field_order() {
+    let sorted = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"value"));
+    let reversed = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_VALUE, Bytes::from_static(b"value"))
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .with_field_order(RapidTlvFieldOrder::Insertion);
+
+    let canonical = sorted.encode_canonical().unwrap();
+    assert_eq!(canonical, reversed.encode_canonical().unwrap());
+    assert_eq!(canonical, sorted.to_bytes().unwrap());
+    assert!(RapidTlvMessage::is_canonical(&canonical));
+    assert!(!RapidTlvMessage::is_canonical(
+        &reversed.to_bytes().unwrap()
+    ));
+    assert_eq!(sorted.digest(), reversed.digest());
+
+    let repeated = sorted
+        .with_repeated_field(FIELD_TAG, Bytes::from_static(b"a"))
+        .with_repeated_field(FIELD_TAG, Bytes::from_static(b"b"));
+    let err = repeated.encode_canonical().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert!(!RapidTlvMessage::is_canonical(
+        &repeated.to_bytes().unwrap()
+    ));
+}
+
+#[test]
+fn test_is_canonical_rejects_trailing_bytes() {
+    let canonical = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .encode_canonical()
+        .unwrap();
+
+    let mut raw = canonical.to_vec();
+    raw.extend_from_slice(&[0, 0]);
+    let len = raw.len() as u32;
+    raw[0..4].copy_from_slice(&len.to_be_bytes());
+    assert!(!RapidTlvMessage::is_canonical(&raw));
+    assert!(!RapidTlvMessage::is_canonical(
+        &canonical[..canonical.len() - 1]
+    ));
+}
+
+#[test]
+fn test_equality_compares_content() {
+    let msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_VALUE, Bytes::from_static(b"value"))
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .with_correlation_id(7);
+    let config = RapidTlvWireConfig::versioned(1).unwrap();
+    let parsed = RapidTlvMessage::parse_with(msg.to_bytes_with(&config).unwrap(), &config).unwrap();
+    let built = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"value"));
+
+    assert_eq!(parsed, built);
+    assert_eq!(parsed.digest(), built.digest());
+    let set: HashSet<_> = [parsed, built.clone()].into_iter().collect();
+    assert_eq!(set.len(), 1);
+
+    let other = built.with_field(FIELD_VALUE, Bytes::from_static(b"other"));
+    assert!(!set.contains(&other));
+}
@@ -0,0 +1,122 @@
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    FLAG_CHECKSUMMED, RapidTlvEndianness, RapidTlvErrorCode, RapidTlvFrameDecoder, RapidTlvMessage,
+    RapidTlvReader, RapidTlvWireConfig, RapidTlvWireFormat, RapidTlvWriter,
+};
+
+use common::{EVT_SET, FIELD_KEY, FIELD_VALUE, set};
+
+#[test]
+fn test_checksum_roundtrip() {
+    let config = RapidTlvWireConfig::legacy().with_checksum(true);
+    let frame = set().to_bytes_with(&config).unwrap();
+    let plain = set().to_bytes().unwrap();
+
+    // The CRC32C of everything before it is appended and counted in the length
+    assert_eq!(frame.len(), plain.len() + 4);
+    assert_eq!(&frame[0..4], &(frame.len() as u32).to_be_bytes());
+    assert_eq!(&frame[4..plain.len()], &plain[4..]);
+    let split = frame.len() - 4;
+    assert_eq!(
+        &frame[split..],
+        &crc32c::crc32c(&frame[..split]).to_be_bytes()
+    );
+
+    let parsed = RapidTlvMessage::parse_with(frame, &config).unwrap();
+    assert_eq!(parsed.field_count(), 2);
+    assert_eq!(parsed.get_field(&FIELD_VALUE).unwrap().value(), b"value");
+
+    let versioned = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_checksum(true);
+    let frame = set().to_bytes_with(&versioned).unwrap();
+    assert_eq!(frame[5], FLAG_CHECKSUMMED);
+    // Flagged frames are verified even if the receiver does not require checksums
+    let parsed =
+        RapidTlvMessage::parse_with(frame, &RapidTlvWireConfig::versioned(1).unwrap()).unwrap();
+    assert_eq!(parsed.get_field(&FIELD_KEY).unwrap().value(), b"key");
+}
+
+#[test]
+fn test_corrupted_frames_are_rejected() {
+    let config = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_checksum(true);
+    let frame = set().to_bytes_with(&config).unwrap();
+
+    // A corrupted length prefix already fails the length check
+    for offset in 4..frame.len() {
+        let mut corrupted = frame.to_vec();
+        corrupted[offset] ^= 0x20;
+        let err = RapidTlvMessage::parse_with(Bytes::from(corrupted), &config).unwrap_err();
+        assert_eq!(err.code(), RapidTlvErrorCode::ChecksumMismatch);
+        assert_eq!(err.offset(), Some(frame.len() - 4));
+    }
+
+    // A receiver that requires checksums rejects frames without one
+    let unchecked = set()
+        .to_bytes_with(&RapidTlvWireConfig::versioned(1).unwrap())
+        .unwrap();
+    let err = RapidTlvMessage::parse_with(unchecked, &config).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(5));
+}
+
+#[test]
+fn test_checksum_edge_cases() {
+    let config = RapidTlvWireConfig::legacy().with_checksum(true);
+
+    // A frame too short to hold its checksum
+    let err =
+        RapidTlvMessage::parse_with(Bytes::from_static(&[0, 0, 0, 7, EVT_SET, 0, 0]), &config)
+            .unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+
+    // A message without fields still carries a checksum
+    let empty = RapidTlvMessage::new(EVT_SET)
+        .to_bytes_with(&config)
+        .unwrap();
+    assert_eq!(empty.len(), 9);
+    let parsed = RapidTlvMessage::parse_with(empty, &config).unwrap();
+    assert_eq!(parsed.field_count(), 0);
+
+    // The checksum follows the byte order of the wire format
+    let little =
+        config.with_format(RapidTlvWireFormat::new().with_endianness(RapidTlvEndianness::Little));
+    let frame = set().to_bytes_with(&little).unwrap();
+    let split = frame.len() - 4;
+    assert_eq!(
+        &frame[split..],
+        &crc32c::crc32c(&frame[..split]).to_le_bytes()
+    );
+    assert!(RapidTlvMessage::parse_with(frame, &little).is_ok());
+}
+
+#[test]
+fn test_checksummed_transports() {
+    let config = RapidTlvWireConfig::legacy().with_checksum(true);
+
+    let mut writer = RapidTlvWriter::new(Vec::new()).with_config(config.clone());
+    writer.write_message(&set()).unwrap();
+    writer
+        .write_message(&RapidTlvMessage::new(EVT_SET))
+        .unwrap();
+    let stream = writer.into_inner().unwrap();
+
+    let mut decoder = RapidTlvFrameDecoder::new().with_config(config.clone());
+    let messages = decoder.feed(&stream).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].field_count(), 0);
+
+    let mut corrupted = stream.clone();
+    corrupted[10] ^= 0x01;
+    let mut reader = RapidTlvReader::new(corrupted.as_slice()).with_config(config.clone());
+    let err = reader.read_message().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ChecksumMismatch);
+
+    // Without a header flag, both sides must agree on checksums
+    let err = config.negotiate(&RapidTlvWireConfig::legacy()).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ConfigInvalid);
+}
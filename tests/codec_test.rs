@@ -0,0 +1,78 @@
+#![cfg(feature = "tokio")]
+
+use bytes::{Bytes, BytesMut};
+use futures::{SinkExt, StreamExt};
+use rapid_tlv::{RapidTlvCodec, RapidTlvMessage};
+use tokio_util::codec::{Decoder, Encoder, Framed};
+
+pub const EVT_SET: u8 = 0x10;
+pub const EVT_GET: u8 = 0x11;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+
+#[tokio::test]
+async fn test_framed_roundtrip() {
+    // Send messages through an in-memory duplex stream wrapped in Framed
+    let (client, server) = tokio::io::duplex(16);
+    let mut client = Framed::new(client, RapidTlvCodec::new());
+    let mut server = Framed::new(server, RapidTlvCodec::new());
+
+    let send = tokio::spawn(async move {
+        let set = RapidTlvMessage::new(EVT_SET)
+            .with_field(FIELD_KEY, Bytes::from_static(b"test_key"))
+            .with_field(FIELD_VALUE, Bytes::from(vec![7u8; 1024]));
+        client.send(set).await.unwrap();
+
+        let get = RapidTlvMessage::new(EVT_GET).with_field(FIELD_KEY, Bytes::from_static(b"k"));
+        client.send(get).await.unwrap();
+    });
+
+    let first = server.next().await.unwrap().unwrap();
+    assert_eq!(first.event_type, EVT_SET);
+    assert_eq!(first.get_field(&FIELD_KEY).unwrap().value(), b"test_key");
+    assert_eq!(
+        first.get_field(&FIELD_VALUE).unwrap().value(),
+        &[7u8; 1024][..]
+    );
+
+    let second = server.next().await.unwrap().unwrap();
+    assert_eq!(second.event_type, EVT_GET);
+
+    send.await.unwrap();
+    assert!(server.next().await.is_none());
+}
+
+#[test]
+fn test_max_frame_size() {
+    // The declared length is checked before the frame body arrives
+    let mut codec = RapidTlvCodec::with_max_frame_size(64);
+    let mut src = BytesMut::from(&[0x00, 0x10, 0x00, 0x00, EVT_SET][..]);
+    assert!(codec.decode(&mut src).is_err());
+
+    // Frames within the limit decode normally
+    let mut msg = RapidTlvMessage::new(EVT_SET).with_field(FIELD_KEY, Bytes::from_static(b"k"));
+    let mut src = BytesMut::from(msg.encode().unwrap());
+    let decoded = codec.decode(&mut src).unwrap().unwrap();
+    assert_eq!(decoded.get_field(&FIELD_KEY).unwrap().value(), b"k");
+}
+
+#[test]
+fn test_max_frame_size_above_default() {
+    // 20 MiB is above the default limit of 16 MiB
+    let value = Bytes::from(vec![0xAB; 20 * 1024 * 1024]);
+    let mut msg = RapidTlvMessage::new(EVT_SET).with_field(FIELD_VALUE, value.clone());
+    let frame = msg.encode().unwrap();
+
+    let mut src = BytesMut::from(frame);
+    assert!(RapidTlvCodec::new().decode(&mut src).is_err());
+
+    let mut codec = RapidTlvCodec::with_max_frame_size(64 * 1024 * 1024);
+    let mut src = BytesMut::from(frame);
+    let decoded = codec.decode(&mut src).unwrap().unwrap();
+    assert_eq!(decoded.get_field(&FIELD_VALUE).unwrap().bytes(), &value);
+
+    let mut dst = BytesMut::new();
+    codec.encode(decoded, &mut dst).unwrap();
+    assert_eq!(&dst[..], frame);
+}
@@ -0,0 +1,17 @@
+// Shared by the integration tests; each test crate uses a different subset
+#![allow(dead_code)]
+
+use bytes::Bytes;
+use rapid_tlv::RapidTlvMessage;
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+
+/// A SET of `key` to `value`.
+pub fn set() -> RapidTlvMessage {
+    RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"value"))
+}
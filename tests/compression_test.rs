@@ -0,0 +1,98 @@
+#![cfg(any(feature = "lz4", feature = "zstd"))]
+
+use bytes::Bytes;
+use rapid_tlv::{
+    FLAG_COMPRESSED, RapidTlvCompression, RapidTlvErrorCode, RapidTlvMessage, RapidTlvWireConfig,
+};
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+
+fn set(value: &str) -> RapidTlvMessage {
+    RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"key"))
+        .with_field(FIELD_VALUE, Bytes::from(value.to_string()))
+}
+
+fn json_blob() -> String {
+    let items: Vec<_> = (0..200)
+        .map(|i| format!(r#"{{"id":{i},"name":"item-{i}","tags":["a","b"]}}"#))
+        .collect();
+    format!("[{}]", items.join(","))
+}
+
+fn algorithms() -> Vec<RapidTlvCompression> {
+    vec![
+        #[cfg(feature = "lz4")]
+        RapidTlvCompression::Lz4,
+        #[cfg(feature = "zstd")]
+        RapidTlvCompression::Zstd(0),
+    ]
+}
+
+#[test]
+fn test_large_fields_are_compressed() {
+    let blob = json_blob();
+    for compression in algorithms() {
+        let config = RapidTlvWireConfig::versioned(1)
+            .unwrap()
+            .with_compression(compression);
+        let frame = set(&blob).to_bytes_with(&config).unwrap();
+        assert_eq!(frame[5] & FLAG_COMPRESSED, FLAG_COMPRESSED);
+        assert!(frame.len() < blob.len() / 2);
+
+        // Receivers decompress flagged frames without being configured to compress
+        let receiver = RapidTlvWireConfig::versioned(1).unwrap();
+        let parsed = RapidTlvMessage::parse_with(frame, &receiver).unwrap();
+        assert_eq!(
+            parsed.get_field(&FIELD_VALUE).unwrap().value(),
+            blob.as_bytes()
+        );
+        assert_eq!(parsed.get_field(&FIELD_KEY).unwrap().value(), b"key");
+    }
+}
+
+#[test]
+fn test_small_fields_and_legacy_header_are_not_compressed() {
+    for compression in algorithms() {
+        let config = RapidTlvWireConfig::versioned(1)
+            .unwrap()
+            .with_compression(compression);
+        let frame = set("small").to_bytes_with(&config).unwrap();
+        assert_eq!(frame[5] & FLAG_COMPRESSED, 0);
+
+        // The unversioned header has no flag to mark compressed frames
+        let legacy = RapidTlvWireConfig::legacy()
+            .with_compression(compression)
+            .with_compression_threshold(0);
+        let frame = set(&json_blob()).to_bytes_with(&legacy).unwrap();
+        assert_eq!(frame, set(&json_blob()).to_bytes().unwrap());
+    }
+}
+
+#[test]
+fn test_decompressed_size_is_capped() {
+    let blob = "x".repeat(1 << 20);
+    for compression in algorithms() {
+        let config = RapidTlvWireConfig::versioned(1)
+            .unwrap()
+            .with_compression(compression);
+        let frame = set(&blob).to_bytes_with(&config).unwrap();
+        assert!(frame.len() < 16 * 1024);
+
+        let receiver = RapidTlvWireConfig::versioned(1)
+            .unwrap()
+            .with_max_decompressed_size(64 * 1024);
+        let err = RapidTlvMessage::parse_with(frame.clone(), &receiver).unwrap_err();
+        assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+        assert_eq!(err.offset(), Some(7));
+
+        // A frame that lies about its decompressed size is malformed
+        let mut lying = frame.to_vec();
+        lying[8..12].copy_from_slice(&100u32.to_be_bytes());
+        let err = RapidTlvMessage::parse_with(Bytes::from(lying), &receiver).unwrap_err();
+        assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    }
+}
@@ -0,0 +1,102 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvFrameDecoder, RapidTlvMessage};
+
+pub const EVT_SET: u8 = 0x10;
+pub const EVT_GET: u8 = 0x11;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+
+fn encoded_set(key: &'static [u8], value: &'static [u8]) -> Vec<u8> {
+    let mut msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(key))
+        .with_field(FIELD_VALUE, Bytes::from_static(value));
+    msg.encode().unwrap().to_vec()
+}
+
+#[test]
+fn test_decode_byte_by_byte() {
+    // Feed a single message one byte at a time, including the split header
+    let encoded = encoded_set(b"test_key", b"test_value");
+    let mut decoder = RapidTlvFrameDecoder::new();
+
+    assert_eq!(decoder.bytes_needed(), 5);
+
+    for (i, byte) in encoded.iter().enumerate() {
+        let messages = decoder.feed(&[*byte]).unwrap();
+        if i + 1 < encoded.len() {
+            assert!(messages.is_empty());
+            assert!(decoder.bytes_needed() > 0);
+        } else {
+            assert_eq!(messages.len(), 1);
+            assert_eq!(messages[0].event_type, EVT_SET);
+            assert_eq!(
+                messages[0].get_field(&FIELD_KEY).unwrap().value(),
+                b"test_key"
+            );
+        }
+    }
+
+    assert_eq!(decoder.buffered(), 0);
+}
+
+#[test]
+fn test_decode_multiple_messages_per_chunk() {
+    // Two complete messages plus the first half of a third in one read
+    let first = encoded_set(b"a", b"1");
+    let mut second = RapidTlvMessage::new(EVT_GET).with_field(FIELD_KEY, Bytes::from_static(b"b"));
+    let second = second.encode().unwrap().to_vec();
+    let third = encoded_set(b"c", b"3");
+
+    let mut chunk = Vec::new();
+    chunk.extend_from_slice(&first);
+    chunk.extend_from_slice(&second);
+    chunk.extend_from_slice(&third[..3]);
+
+    let mut decoder = RapidTlvFrameDecoder::new();
+    let messages = decoder.feed(&chunk).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].event_type, EVT_SET);
+    assert_eq!(messages[1].event_type, EVT_GET);
+    assert_eq!(messages[1].get_field(&FIELD_KEY).unwrap().value(), b"b");
+
+    // The header of the third message is still incomplete
+    assert_eq!(decoder.buffered(), 3);
+    assert_eq!(decoder.bytes_needed(), 2);
+
+    decoder.extend(&third[3..6]);
+    assert_eq!(decoder.bytes_needed(), third.len() - 6);
+
+    let messages = decoder.feed(&third[6..]).unwrap();
+    assert_eq!(messages.len(), 1);
+    assert_eq!(messages[0].get_field(&FIELD_VALUE).unwrap().value(), b"3");
+    assert_eq!(decoder.bytes_needed(), 5);
+}
+
+#[test]
+fn test_decode_invalid_declared_length() {
+    // A declared length smaller than the header can never become a valid frame
+    let mut decoder = RapidTlvFrameDecoder::new();
+    let result = decoder.feed(&[0, 0, 0, 2, 0x10]);
+    assert!(result.is_err());
+    assert_eq!(decoder.buffered(), 5);
+    assert!(decoder.decode().is_err());
+
+    decoder.clear();
+    assert_eq!(decoder.buffered(), 0);
+}
+
+#[test]
+fn test_decode_skips_unparseable_frame() {
+    // The length prefix is intact, but the field claims more bytes than the frame has
+    let mut decoder = RapidTlvFrameDecoder::new();
+    decoder.extend(&[0, 0, 0, 10, EVT_SET, FIELD_KEY as u8, 0, 0, 0, 9]);
+    let encoded = encoded_set(b"k", b"v");
+    decoder.extend(&encoded);
+
+    assert!(decoder.decode().is_err());
+    assert_eq!(decoder.buffered(), encoded.len());
+    let msg = decoder.decode().unwrap().unwrap();
+    assert_eq!(msg.get_field(&FIELD_KEY).unwrap().value(), b"k");
+    assert_eq!(decoder.buffered(), 0);
+}
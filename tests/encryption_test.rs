@@ -0,0 +1,145 @@
+#![cfg(feature = "encryption")]
+
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    RapidTlvCipher, RapidTlvEncryption, RapidTlvErrorCode, RapidTlvMessage, RapidTlvParseLimits,
+};
+
+use common::{EVT_SET, FIELD_KEY, FIELD_VALUE, set};
+
+pub const FIELD_SEALED: u16 = 0x20;
+
+const KEY: [u8; 32] = [7; 32];
+
+#[test]
+fn test_selected_fields_roundtrip() {
+    for cipher in [RapidTlvCipher::ChaCha20Poly1305, RapidTlvCipher::Aes256Gcm] {
+        let encryption = RapidTlvEncryption::fields(cipher, KEY, &[FIELD_VALUE]);
+        let encrypted = encryption.encrypt(&set().with_correlation_id(9)).unwrap();
+
+        // Routing information stays readable
+        assert_eq!(encrypted.event_type, EVT_SET);
+        assert_eq!(encrypted.get_field(&FIELD_KEY).unwrap().value(), b"key");
+        let sealed = encrypted.get_field(&FIELD_VALUE).unwrap().value();
+        assert_eq!(sealed.len(), 12 + b"value".len() + 16);
+        assert!(!sealed.windows(5).any(|w| w == b"value"));
+
+        // The encrypted message is an ordinary message on the wire
+        let frame = encrypted.to_bytes().unwrap();
+        let parsed = RapidTlvMessage::parse(frame).unwrap();
+        let decrypted = encryption.decrypt(&parsed).unwrap();
+        assert_eq!(decrypted.to_bytes().unwrap(), set().to_bytes().unwrap());
+        assert_eq!(
+            encryption.decrypt(&encrypted).unwrap().correlation_id(),
+            Some(9)
+        );
+    }
+}
+
+#[test]
+fn test_whole_section_roundtrip() {
+    let encryption = RapidTlvEncryption::section(RapidTlvCipher::Aes256Gcm, KEY, FIELD_SEALED);
+    let encrypted = encryption.encrypt(&set()).unwrap();
+    assert_eq!(encrypted.event_type, EVT_SET);
+    assert_eq!(encrypted.field_count(), 1);
+    assert!(encrypted.get_field(&FIELD_KEY).is_none());
+
+    let decrypted = encryption.decrypt(&encrypted).unwrap();
+    assert_eq!(decrypted.field_count(), 2);
+    assert_eq!(decrypted.get_field(&FIELD_VALUE).unwrap().value(), b"value");
+
+    let err = encryption.decrypt(&set()).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_SEALED));
+}
+
+#[test]
+fn test_decryption_keeps_parse_limits() {
+    // The decrypted section is parsed under the limits of its message
+    let encryption = RapidTlvEncryption::section(RapidTlvCipher::Aes256Gcm, KEY, FIELD_SEALED);
+    let frame = encryption.encrypt(&set()).unwrap().to_bytes().unwrap();
+    let limits = RapidTlvParseLimits::new().with_max_field_count(1);
+    let parsed = RapidTlvMessage::parse_with_limits(frame, &limits).unwrap();
+    let err = encryption.decrypt(&parsed).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+
+    // and the decrypted message inherits them
+    let limits = RapidTlvParseLimits::new().with_max_nesting_depth(1);
+    for encryption in [
+        encryption,
+        RapidTlvEncryption::fields(RapidTlvCipher::ChaCha20Poly1305, KEY, &[FIELD_VALUE]),
+    ] {
+        let frame = encryption.encrypt(&set()).unwrap().to_bytes().unwrap();
+        let parsed = RapidTlvMessage::parse_with_limits(frame, &limits).unwrap();
+        assert_eq!(encryption.decrypt(&parsed).unwrap().max_nesting_depth(), 1);
+    }
+}
+
+#[test]
+fn test_tampering_is_detected() {
+    let encryption =
+        RapidTlvEncryption::fields(RapidTlvCipher::ChaCha20Poly1305, KEY, &[FIELD_VALUE]);
+    let encrypted = encryption.encrypt(&set()).unwrap();
+    let sealed = encrypted.get_field(&FIELD_VALUE).unwrap().bytes().clone();
+
+    let mut flipped = sealed.to_vec();
+    flipped[14] ^= 0x01;
+    let tampered = encrypted
+        .clone()
+        .with_field(FIELD_VALUE, Bytes::from(flipped));
+    let err = encryption.decrypt(&tampered).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+    assert_eq!(err.field_type(), Some(FIELD_VALUE));
+
+    // Values are bound to their event type
+    let mut moved = encrypted.clone();
+    moved.event_type = 0x11;
+    let err = encryption.decrypt(&moved).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+
+    // Values are bound to the rest of their message
+    let alice = encryption.encrypt(&set()).unwrap();
+    let bob = encryption
+        .encrypt(&set().with_field(FIELD_KEY, Bytes::from_static(b"bob")))
+        .unwrap();
+    let swapped = alice.with_field(
+        FIELD_VALUE,
+        bob.get_field(&FIELD_VALUE).unwrap().bytes().clone(),
+    );
+    let err = encryption.decrypt(&swapped).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+    let relabeled = bob.with_field(FIELD_KEY, Bytes::from_static(b"key"));
+    let err = encryption.decrypt(&relabeled).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+
+    let wrong_key =
+        RapidTlvEncryption::fields(RapidTlvCipher::ChaCha20Poly1305, [8; 32], &[FIELD_VALUE]);
+    let err = wrong_key.decrypt(&encrypted).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::AuthenticationFailed);
+}
+
+#[test]
+fn test_encryption_edge_cases() {
+    let wide_type = 0x0102;
+    let encryption =
+        RapidTlvEncryption::fields(RapidTlvCipher::Aes256Gcm, KEY, &[FIELD_VALUE, wide_type]);
+
+    // Empty and repeated values, and field types above 255
+    let msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_VALUE, Bytes::new())
+        .with_repeated_field(wide_type, Bytes::from_static(b"a"))
+        .with_repeated_field(wide_type, Bytes::from_static(b"b"));
+    let encrypted = encryption.encrypt(&msg).unwrap();
+    assert_eq!(encrypted.get_field(&FIELD_VALUE).unwrap().value().len(), 28);
+    let sealed: Vec<_> = encrypted.get_fields(&wide_type).collect();
+    assert_ne!(sealed[0].value(), sealed[1].value());
+    assert_eq!(encryption.decrypt(&encrypted).unwrap(), msg);
+
+    // A value too short for nonce and tag was never sealed
+    let truncated = encrypted.with_field(FIELD_VALUE, Bytes::from_static(&[0; 27]));
+    let err = encryption.decrypt(&truncated).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_VALUE));
+}
@@ -0,0 +1,116 @@
+use bytes::Bytes;
+use rapid_tlv::{
+    EVT_ERROR, FIELD_ERROR_CODE, FIELD_ERROR_FIELD_TYPE, FIELD_ERROR_MESSAGE, FIELD_ERROR_OFFSET,
+    RapidTlvError, RapidTlvErrorCode, RapidTlvMessage,
+};
+
+#[test]
+fn test_error_code_conversion() {
+    // Codes convert to their wire value and back
+    assert_eq!(u16::from(RapidTlvErrorCode::Malformed), 0x02);
+    assert_eq!(u16::from(RapidTlvErrorCode::WriteFailed), 0x52);
+
+    let code = RapidTlvErrorCode::try_from(0x13).unwrap();
+    assert_eq!(code, RapidTlvErrorCode::ValueTooLarge);
+
+    // Unknown values are rejected
+    let err = RapidTlvErrorCode::try_from(0x99).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+}
+
+#[test]
+fn test_parse_error_details() {
+    // Field with invalid length (field length exceeds message length)
+    let invalid_field_length = Bytes::from_static(&[
+        0, 0, 0, 10,   // Message length (10 bytes)
+        0x01, // Event type (Set)
+        0x03, // Field type (TTL)
+        0, 0, 0, 20, // Field length (20 bytes, which exceeds message length)
+    ]);
+    let err = RapidTlvMessage::parse(invalid_field_length).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(5));
+    assert_eq!(err.field_type(), Some(0x03));
+    assert_eq!(err.message(), "Not enough data for field value");
+
+    let display = err.to_string();
+    assert!(display.starts_with("Malformed (0x0002)"));
+    assert!(display.contains("at offset 5"));
+    assert!(display.contains("in field type 3"));
+}
+
+#[test]
+fn test_error_is_std_error() {
+    // Errors can be boxed and propagated with ?
+    fn parse(raw: &'static [u8]) -> Result<RapidTlvMessage, Box<dyn std::error::Error>> {
+        Ok(RapidTlvMessage::parse(Bytes::from_static(raw))?)
+    }
+
+    let err = parse(&[0, 0, 0, 5]).unwrap_err();
+    let err = err.downcast_ref::<RapidTlvError>().unwrap();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(0));
+}
+
+#[test]
+fn test_error_response_roundtrip() {
+    // Turn a parse error into a response frame and read it back on the other side
+    let err = RapidTlvMessage::parse(Bytes::from_static(&[0, 0, 0, 10, 0x01, 0x03, 0, 0, 0, 20]))
+        .unwrap_err();
+
+    let mut response = err.to_message();
+    assert_eq!(response.event_type, EVT_ERROR);
+    assert_eq!(
+        response.get_field(&FIELD_ERROR_CODE).unwrap().value(),
+        &[0x00, 0x02]
+    );
+
+    let parsed =
+        RapidTlvMessage::parse(Bytes::copy_from_slice(response.encode().unwrap())).unwrap();
+    let decoded = RapidTlvError::from_message(&parsed).unwrap();
+    assert_eq!(decoded.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(decoded.message(), err.message());
+    assert_eq!(decoded.offset(), Some(5));
+    assert_eq!(decoded.field_type(), Some(0x03));
+
+    // Field types above 255 take two bytes
+    let err =
+        RapidTlvError::new(RapidTlvErrorCode::ValueTooLarge, "wide".into()).with_field_type(0x0102);
+    let response = err.to_message();
+    assert_eq!(
+        response.get_field(&FIELD_ERROR_FIELD_TYPE).unwrap().value(),
+        &[0x01, 0x02]
+    );
+    assert_eq!(
+        RapidTlvError::from_message(&response).unwrap().field_type(),
+        Some(0x0102)
+    );
+
+    // An offset the response cannot carry is left out instead of truncated
+    #[cfg(target_pointer_width = "64")]
+    {
+        let err = RapidTlvError::new(RapidTlvErrorCode::Malformed, "far".into())
+            .at_offset(u32::MAX as usize + 5);
+        let response = err.to_message();
+        assert!(response.get_field(&FIELD_ERROR_OFFSET).is_none());
+        assert_eq!(
+            RapidTlvError::from_message(&response).unwrap().offset(),
+            None
+        );
+    }
+}
+
+#[test]
+fn test_invalid_error_response() {
+    // Only EVT_ERROR messages can be read as errors
+    let err = RapidTlvError::from_message(&RapidTlvMessage::new(0x10)).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::InvalidEventType);
+
+    // The code field must be exactly two bytes
+    let msg = RapidTlvMessage::new(EVT_ERROR)
+        .with_field(FIELD_ERROR_CODE, Bytes::from_static(&[0x02]))
+        .with_field(FIELD_ERROR_MESSAGE, Bytes::from_static(b"oops"));
+    let err = RapidTlvError::from_message(&msg).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_ERROR_CODE));
+}
@@ -0,0 +1,125 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvFieldOrder, RapidTlvMessage};
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_TTL: u16 = 0x03;
+pub const FIELD_ID: u16 = 0x08;
+
+// Fields on the wire in the order TTL, KEY, TTL
+const UNSORTED: [u8; 23] = [
+    0x00,
+    0x00,
+    0x00,
+    0x17,    // Message length = 23 bytes
+    EVT_SET, // Event type
+    FIELD_TTL as u8,
+    0x00,
+    0x00,
+    0x00,
+    0x01,
+    0x3C, // TTL = 60
+    FIELD_KEY as u8,
+    0x00,
+    0x00,
+    0x00,
+    0x01,
+    b'k', // Key
+    FIELD_TTL as u8,
+    0x00,
+    0x00,
+    0x00,
+    0x01,
+    0x1E, // TTL = 30
+];
+
+fn types(msg: &RapidTlvMessage) -> Vec<u16> {
+    msg.fields().map(|f| *f.field_type()).collect()
+}
+
+#[test]
+fn test_default_order_is_by_type() {
+    let mut msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_ID, Bytes::from_static(b"1"))
+        .with_field(FIELD_KEY, Bytes::from_static(b"k"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"v"));
+
+    assert_eq!(msg.field_order(), RapidTlvFieldOrder::ByType);
+    assert_eq!(types(&msg), vec![FIELD_KEY, FIELD_VALUE, FIELD_ID]);
+    assert_eq!(msg.field_count(), 3);
+
+    let encoded = msg.encode().unwrap();
+    assert_eq!(encoded[5], FIELD_KEY as u8);
+}
+
+#[test]
+fn test_insertion_order_roundtrip() {
+    let mut msg = RapidTlvMessage::new(EVT_SET)
+        .with_field_order(RapidTlvFieldOrder::Insertion)
+        .with_field(FIELD_ID, Bytes::from_static(b"1"))
+        .with_field(FIELD_KEY, Bytes::from_static(b"k"))
+        .with_repeated_field(FIELD_ID, Bytes::from_static(b"2"));
+
+    assert_eq!(types(&msg), vec![FIELD_ID, FIELD_KEY, FIELD_ID]);
+
+    let mut parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap()))
+        .unwrap()
+        .with_field_order(RapidTlvFieldOrder::Insertion);
+    assert_eq!(types(&parsed), vec![FIELD_ID, FIELD_KEY, FIELD_ID]);
+    assert_eq!(parsed.encode().unwrap(), msg.encode().unwrap());
+}
+
+#[test]
+fn test_wire_order_preserved_for_proxying() {
+    let mut parsed = RapidTlvMessage::parse(Bytes::from_static(&UNSORTED))
+        .unwrap()
+        .with_field_order(RapidTlvFieldOrder::Insertion);
+
+    // Re-encoding reproduces the received bytes exactly
+    assert_eq!(parsed.encode().unwrap(), &UNSORTED[..]);
+    assert_eq!(parsed.field_count(), 3);
+
+    // New fields are appended after the original wire order
+    parsed.add_field(FIELD_VALUE, Bytes::from_static(b"v"));
+    assert_eq!(
+        types(&parsed),
+        vec![FIELD_TTL, FIELD_KEY, FIELD_TTL, FIELD_VALUE]
+    );
+    let encoded = parsed.encode().unwrap();
+    assert_eq!(&encoded[4..UNSORTED.len()], &UNSORTED[4..]);
+
+    // Switching back to type order sorts the fields
+    parsed.set_field_order(RapidTlvFieldOrder::ByType);
+    assert_eq!(
+        types(&parsed),
+        vec![FIELD_KEY, FIELD_VALUE, FIELD_TTL, FIELD_TTL]
+    );
+}
+
+#[test]
+fn test_by_type_order_across_full_type_range() {
+    // Field types spread over the whole u8 range, added out of order
+    let mut msg = RapidTlvMessage::new(EVT_SET);
+    for field_type in [0xFF, 0x00, 0x80, 0x40, 0x3F, 0x80, 0xC1] {
+        msg.add_repeated_field(field_type, Bytes::from_static(b"x"));
+    }
+
+    assert_eq!(types(&msg), vec![0x00, 0x3F, 0x40, 0x80, 0x80, 0xC1, 0xFF]);
+    assert!(msg.get_field(&0x41).is_none());
+    assert_eq!(msg.get_fields(&0x80).count(), 2);
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(types(&parsed), types(&msg));
+
+    // Two-byte field types sort after the one-byte range
+    for field_type in [0x0200, 0x0100, 0x0200] {
+        msg.add_repeated_field(field_type, Bytes::from_static(b"w"));
+    }
+    assert_eq!(types(&msg)[6..], [0xFF, 0x0100, 0x0200, 0x0200]);
+    assert_eq!(msg.get_fields(&0x0200).count(), 2);
+    assert!(msg.remove_field(0x0100));
+    assert!(msg.get_field(&0x0100).is_none());
+    assert_eq!(types(&msg)[7..], [0x0200, 0x0200]);
+}
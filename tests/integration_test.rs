@@ -5,14 +5,14 @@ pub const EVT_SET: u8 = 0x10;
 pub const EVT_GET: u8 = 0x11;
 pub const EVT_DELETE: u8 = 0x12;
 
-pub const FIELD_KEY: u8 = 0x01;
-pub const FIELD_VALUE: u8 = 0x02;
-pub const FIELD_TTL: u8 = 0x03;
-pub const FIELD_PERSIST: u8 = 0x04;
-pub const FIELD_GROUP: u8 = 0x05;
-pub const FIELD_TIMESTAMP: u8 = 0x06;
-pub const FIELD_VERSION: u8 = 0x07;
-pub const FIELD_ID: u8 = 0x08;
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_TTL: u16 = 0x03;
+pub const FIELD_PERSIST: u16 = 0x04;
+pub const FIELD_GROUP: u16 = 0x05;
+pub const FIELD_TIMESTAMP: u16 = 0x06;
+pub const FIELD_VERSION: u16 = 0x07;
+pub const FIELD_ID: u16 = 0x08;
 
 #[test]
 fn test_message_creation() {
@@ -253,7 +253,7 @@ fn test_field_type_conversion() {
         let field_type_byte = encoded[0];
 
         // Convert back to RapidTlvFieldType
-        let decoded_type = field_type_byte;
+        let decoded_type = u16::from(field_type_byte);
 
         // Verify the field type
         assert_eq!(decoded_type, *field_type);
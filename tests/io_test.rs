@@ -0,0 +1,122 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvMessage, RapidTlvReader, RapidTlvWriter};
+use std::io::{Cursor, IoSlice, Write};
+
+pub const EVT_SET: u8 = 0x10;
+pub const EVT_DELETE: u8 = 0x12;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+
+#[test]
+fn test_write_then_read() {
+    // Write two messages and read them back until a clean EOF
+    let mut writer = RapidTlvWriter::new(Vec::new());
+
+    let set = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"test_key"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"test_value"));
+    writer.write_message(&set).unwrap();
+
+    let delete =
+        RapidTlvMessage::new(EVT_DELETE).with_field(FIELD_KEY, Bytes::from_static(b"test_key"));
+    writer.write_message(&delete).unwrap();
+    writer.flush().unwrap();
+
+    let written = writer.into_inner().unwrap();
+
+    // The streamed output matches the regular encoding
+    let mut expected = set.clone().encode().unwrap().to_vec();
+    expected.extend_from_slice(delete.clone().encode().unwrap());
+    assert_eq!(written, expected);
+
+    let mut reader = RapidTlvReader::new(Cursor::new(written));
+    let first = reader.read_message().unwrap().unwrap();
+    assert_eq!(first.event_type, EVT_SET);
+    assert_eq!(
+        first.get_field(&FIELD_VALUE).unwrap().value(),
+        b"test_value"
+    );
+
+    let second = reader.read_message().unwrap().unwrap();
+    assert_eq!(second.event_type, EVT_DELETE);
+
+    assert!(reader.read_message().unwrap().is_none());
+}
+
+#[test]
+fn test_reader_iterator() {
+    let mut encoded = Vec::new();
+    for key in [&b"a"[..], b"b", b"c"] {
+        let mut msg =
+            RapidTlvMessage::new(EVT_SET).with_field(FIELD_KEY, Bytes::copy_from_slice(key));
+        encoded.extend_from_slice(msg.encode().unwrap());
+    }
+
+    let keys: Vec<Vec<u8>> = RapidTlvReader::new(Cursor::new(encoded))
+        .map(|msg| msg.unwrap().get_field(&FIELD_KEY).unwrap().value().to_vec())
+        .collect();
+    assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+}
+
+#[test]
+fn test_truncated_trailing_frame() {
+    let mut msg = RapidTlvMessage::new(EVT_SET).with_field(FIELD_KEY, Bytes::from_static(b"k"));
+    let encoded = msg.encode().unwrap().to_vec();
+
+    // Stream ends inside the frame body
+    let mut reader = RapidTlvReader::new(Cursor::new(encoded[..encoded.len() - 1].to_vec()));
+    let err = reader.read_message().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::IncompleteMessage);
+    assert_eq!(err.offset(), Some(encoded.len() - 1));
+
+    // Stream ends inside the length prefix
+    let mut reader = RapidTlvReader::new(Cursor::new(encoded[..2].to_vec()));
+    let err = reader.read_message().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::IncompleteMessage);
+
+    // An empty stream is a clean EOF
+    let mut reader = RapidTlvReader::new(Cursor::new(Vec::new()));
+    assert!(reader.read_message().unwrap().is_none());
+}
+
+// Records the addresses of the slices it is handed, one partial write at a time
+#[derive(Default)]
+struct SliceRecorder {
+    written: Vec<u8>,
+    slices: Vec<*const u8>,
+}
+
+impl Write for SliceRecorder {
+    fn write(&mut self, buf: &[u8]) -> std::io::Result<usize> {
+        self.write_vectored(&[IoSlice::new(buf)])
+    }
+
+    fn write_vectored(&mut self, bufs: &[IoSlice<'_>]) -> std::io::Result<usize> {
+        let first = bufs.iter().find(|b| !b.is_empty()).unwrap();
+        // Stop halfway to exercise resuming a partial write
+        let n = first.len().div_ceil(2);
+        self.slices.push(first.as_ptr());
+        self.written.extend_from_slice(&first[..n]);
+        Ok(n)
+    }
+
+    fn flush(&mut self) -> std::io::Result<()> {
+        Ok(())
+    }
+}
+
+#[test]
+fn test_writer_does_not_copy_payloads() {
+    let value = Bytes::from(vec![7u8; 4096]);
+    let set = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"test_key"))
+        .with_field(FIELD_VALUE, value.clone());
+
+    let mut writer = RapidTlvWriter::new(SliceRecorder::default());
+    writer.write_message(&set).unwrap();
+    let recorder = writer.into_inner().unwrap();
+
+    assert_eq!(recorder.written, set.to_bytes().unwrap());
+    assert!(recorder.slices.contains(&value.as_ptr()));
+}
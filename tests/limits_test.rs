@@ -0,0 +1,129 @@
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    RapidTlvErrorCode, RapidTlvFrameDecoder, RapidTlvMessage, RapidTlvMessageRef,
+    RapidTlvParseLimits, RapidTlvReader, RapidTlvWireConfig,
+};
+
+use common::{EVT_SET, FIELD_VALUE};
+
+pub const FIELD_NESTED: u16 = 0x03;
+
+// A SET with a 100-byte value
+fn large_set() -> RapidTlvMessage {
+    common::set().with_field(FIELD_VALUE, Bytes::from(vec![0u8; 100]))
+}
+
+#[test]
+fn test_field_limits() {
+    let frame = large_set().to_bytes().unwrap();
+    assert!(RapidTlvMessage::parse(frame.clone()).is_ok());
+
+    let limits = RapidTlvParseLimits::new().with_max_field_size(64);
+    let err = RapidTlvMessage::parse_with_limits(frame.clone(), &limits).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+    assert_eq!(err.field_type(), Some(FIELD_VALUE));
+    assert_eq!(err.offset(), Some(13));
+
+    let limits = RapidTlvParseLimits::new().with_max_field_count(1);
+    let err = RapidTlvMessageRef::parse_with_limits(&frame, &limits).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+
+    let limits = RapidTlvParseLimits::new().with_max_frame_size(frame.len() - 1);
+    let config = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_limits(limits);
+    let versioned = large_set()
+        .to_bytes_with(&RapidTlvWireConfig::versioned(1).unwrap())
+        .unwrap();
+    let err = RapidTlvMessage::parse_with(versioned, &config).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+    assert_eq!(err.offset(), Some(0));
+}
+
+#[test]
+fn test_limits_are_inclusive() {
+    let frame = large_set().to_bytes().unwrap();
+    let limits = RapidTlvParseLimits::new()
+        .with_max_frame_size(frame.len())
+        .with_max_field_size(100)
+        .with_max_field_count(2);
+    let parsed = RapidTlvMessage::parse_with_limits(frame.clone(), &limits).unwrap();
+    assert_eq!(parsed, large_set());
+    assert!(RapidTlvMessageRef::parse_with_limits(&frame, &limits).is_ok());
+
+    let mut decoder =
+        RapidTlvFrameDecoder::new().with_config(RapidTlvWireConfig::legacy().with_limits(limits));
+    assert_eq!(decoder.feed(&frame).unwrap().len(), 1);
+
+    // Empty frames pass even the tightest limits
+    let tight = RapidTlvParseLimits::new()
+        .with_max_frame_size(5)
+        .with_max_field_size(0)
+        .with_max_field_count(0);
+    let empty = RapidTlvMessage::new(EVT_SET).to_bytes().unwrap();
+    assert!(RapidTlvMessage::parse_with_limits(empty, &tight).is_ok());
+}
+
+#[test]
+#[cfg(any(feature = "lz4", feature = "zstd"))]
+fn test_decompressed_size_is_capped_by_frame_limit() {
+    #[cfg(feature = "lz4")]
+    let compression = rapid_tlv::RapidTlvCompression::Lz4;
+    #[cfg(not(feature = "lz4"))]
+    let compression = rapid_tlv::RapidTlvCompression::Zstd(0);
+
+    // 100 zero bytes compress well below the frame limit
+    let config = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_compression(compression)
+        .with_compression_threshold(0);
+    let frame = large_set().to_bytes_with(&config).unwrap();
+    assert!(frame.len() < 100);
+    assert!(RapidTlvMessage::parse_with(frame.clone(), &config).is_ok());
+
+    let limits = RapidTlvParseLimits::new().with_max_frame_size(100);
+    let err = RapidTlvMessage::parse_with(frame, &config.with_limits(limits)).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+    assert!(err.message().contains("decompress"), "{err}");
+}
+
+#[test]
+fn test_nesting_limit_is_inherited() {
+    let inner =
+        RapidTlvMessage::new(EVT_SET).with_field(FIELD_NESTED, large_set().to_bytes().unwrap());
+    let outer = RapidTlvMessage::new(EVT_SET).with_field(FIELD_NESTED, inner.to_bytes().unwrap());
+
+    let limits = RapidTlvParseLimits::new().with_max_nesting_depth(1);
+    let parsed = RapidTlvMessage::parse_with_limits(outer.to_bytes().unwrap(), &limits).unwrap();
+    assert_eq!(parsed.max_nesting_depth(), 1);
+    let nested = parsed.get_message_field(FIELD_NESTED).unwrap().unwrap();
+    let err = nested.get_message_field(FIELD_NESTED).unwrap_err();
+    assert_eq!(err.field_type(), Some(FIELD_NESTED));
+
+    // Field limits apply to nested messages as well
+    let limits = RapidTlvParseLimits::new().with_max_field_count(1);
+    let parsed = RapidTlvMessage::parse_with_limits(inner.to_bytes().unwrap(), &limits).unwrap();
+    let err = parsed.get_message_field(FIELD_NESTED).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+}
+
+#[test]
+fn test_hostile_length_prefix_is_rejected_before_allocating() {
+    // Claims a 4 GiB frame
+    static HOSTILE: [u8; 6] = [0xFF, 0xFF, 0xFF, 0xFF, EVT_SET, 0x01];
+    let hostile = &HOSTILE;
+
+    let mut decoder = RapidTlvFrameDecoder::new();
+    let err = decoder.feed(hostile).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+    assert!(decoder.buffer_mut().capacity() < 1024);
+
+    let mut reader = RapidTlvReader::new(&hostile[..]);
+    let err = reader.read_message().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+
+    let err = RapidTlvMessage::parse(Bytes::from_static(hostile)).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+}
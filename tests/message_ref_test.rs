@@ -0,0 +1,88 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvMessage, RapidTlvMessageRef};
+use std::alloc::{GlobalAlloc, Layout, System};
+use std::cell::Cell;
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_GROUP: u16 = 0x05;
+
+// Counts allocations made by the current thread
+struct CountingAlloc;
+
+thread_local! {
+    static ALLOCATIONS: Cell<usize> = const { Cell::new(0) };
+}
+
+unsafe impl GlobalAlloc for CountingAlloc {
+    unsafe fn alloc(&self, layout: Layout) -> *mut u8 {
+        ALLOCATIONS.with(|a| a.set(a.get() + 1));
+        unsafe { System.alloc(layout) }
+    }
+
+    unsafe fn dealloc(&self, ptr: *mut u8, layout: Layout) {
+        unsafe { System.dealloc(ptr, layout) }
+    }
+}
+
+#[global_allocator]
+static GLOBAL: CountingAlloc = CountingAlloc;
+
+fn encoded() -> Vec<u8> {
+    let mut msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"test_key"))
+        .with_field(FIELD_VALUE, Bytes::from_static(b"test_value"))
+        .with_repeated_field(FIELD_GROUP, Bytes::from_static(b"g1"))
+        .with_repeated_field(FIELD_GROUP, Bytes::from_static(b"g2"));
+    msg.encode().unwrap().to_vec()
+}
+
+#[test]
+fn test_message_ref_lookup() {
+    let raw = encoded();
+    let view = RapidTlvMessageRef::parse(&raw).unwrap();
+
+    assert_eq!(view.event_type(), EVT_SET);
+    assert_eq!(view.get_field(FIELD_KEY), Some(&b"test_key"[..]));
+    assert_eq!(view.get_field(FIELD_GROUP), Some(&b"g2"[..]));
+    assert!(view.get_field(0x42).is_none());
+    assert_eq!(view.field_count(), 4);
+
+    let groups: Vec<&[u8]> = view.get_fields(FIELD_GROUP).collect();
+    assert_eq!(groups, vec![&b"g1"[..], &b"g2"[..]]);
+
+    let types: Vec<u16> = view.fields().map(|f| f.field_type()).collect();
+    assert_eq!(
+        types,
+        vec![FIELD_KEY, FIELD_VALUE, FIELD_GROUP, FIELD_GROUP]
+    );
+}
+
+#[test]
+fn test_message_ref_does_not_allocate() {
+    let raw = encoded();
+
+    let before = ALLOCATIONS.with(Cell::get);
+    let view = RapidTlvMessageRef::parse(&raw).unwrap();
+    let routed = (view.event_type(), view.get_field(FIELD_KEY).unwrap().len());
+    let after = ALLOCATIONS.with(Cell::get);
+
+    assert_eq!(routed, (EVT_SET, 8));
+    assert_eq!(after, before);
+}
+
+#[test]
+fn test_message_ref_to_message() {
+    let raw = encoded();
+    let mut msg = RapidTlvMessageRef::parse(&raw).unwrap().to_message();
+
+    assert_eq!(msg.event_type, EVT_SET);
+    assert_eq!(msg.get_fields(&FIELD_GROUP).count(), 2);
+    assert_eq!(msg.encode().unwrap(), &raw[..]);
+
+    // Validation matches Message::parse
+    let err = RapidTlvMessageRef::parse(&raw[..raw.len() - 1]).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+}
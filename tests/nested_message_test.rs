@@ -0,0 +1,79 @@
+use bytes::Bytes;
+use rapid_tlv::{DEFAULT_MAX_NESTING_DEPTH, RapidTlvErrorCode, RapidTlvMessage};
+
+pub const EVT_SET: u8 = 0x10;
+pub const EVT_BATCH: u8 = 0x20;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+pub const FIELD_RECORD: u16 = 0x09;
+pub const FIELD_EVENT: u16 = 0x0A;
+
+fn set(key: &'static [u8]) -> RapidTlvMessage {
+    RapidTlvMessage::new(EVT_SET).with_field(FIELD_KEY, Bytes::from_static(key))
+}
+
+#[test]
+fn test_nested_message_roundtrip() {
+    let record = RapidTlvMessage::new(0x30).with_field(FIELD_VALUE, Bytes::from_static(b"sub"));
+    let mut msg = set(b"outer")
+        .with_message_field(FIELD_RECORD, record)
+        .unwrap();
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    let nested = parsed.get_message_field(FIELD_RECORD).unwrap().unwrap();
+    assert_eq!(nested.event_type, 0x30);
+    assert_eq!(nested.get_field(&FIELD_VALUE).unwrap().value(), b"sub");
+
+    // The nested value points into the parent's buffer
+    let outer = parsed.get_field(&FIELD_RECORD).unwrap().value().as_ptr();
+    let inner = nested.get_field(&FIELD_VALUE).unwrap().value().as_ptr();
+    assert_eq!(inner, outer.wrapping_add(10));
+
+    assert!(parsed.get_message_field(0x42).unwrap().is_none());
+}
+
+#[test]
+fn test_batch_of_nested_messages() {
+    let mut batch = RapidTlvMessage::new(EVT_BATCH);
+    for key in [&b"a"[..], b"b", b"c"] {
+        batch
+            .add_repeated_message_field(FIELD_EVENT, set(key))
+            .unwrap();
+    }
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(batch.encode().unwrap())).unwrap();
+    let keys: Vec<Vec<u8>> = parsed
+        .get_message_fields(FIELD_EVENT)
+        .map(|m| m.unwrap().get_field(&FIELD_KEY).unwrap().value().to_vec())
+        .collect();
+    assert_eq!(keys, vec![b"a".to_vec(), b"b".to_vec(), b"c".to_vec()]);
+}
+
+#[test]
+fn test_nesting_depth_limit() {
+    // Build a chain nested three levels deep
+    let mut msg = set(b"leaf");
+    for _ in 0..3 {
+        msg = RapidTlvMessage::new(EVT_SET)
+            .with_message_field(FIELD_RECORD, msg)
+            .unwrap();
+    }
+    let encoded = Bytes::copy_from_slice(msg.encode().unwrap());
+
+    let mut parsed = RapidTlvMessage::parse(encoded.clone()).unwrap();
+    assert_eq!(parsed.max_nesting_depth(), DEFAULT_MAX_NESTING_DEPTH);
+    parsed.set_max_nesting_depth(2);
+
+    // Two levels are allowed, the third is rejected
+    let first = parsed.get_message_field(FIELD_RECORD).unwrap().unwrap();
+    let second = first.get_message_field(FIELD_RECORD).unwrap().unwrap();
+    let err = second.get_message_field(FIELD_RECORD).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_RECORD));
+
+    // A malformed nested value is reported, not panicked on
+    let broken = set(b"k").with_field(FIELD_RECORD, Bytes::from_static(&[0, 0, 0, 9, 1]));
+    let err = broken.get_message_field(FIELD_RECORD).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+}
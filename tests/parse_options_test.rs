@@ -0,0 +1,138 @@
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    RapidTlvErrorCode, RapidTlvFieldTypeWidth, RapidTlvFrameDecoder, RapidTlvMessage,
+    RapidTlvParseOptions, RapidTlvWireConfig, RapidTlvWireFormat,
+};
+
+use common::{FIELD_KEY, FIELD_VALUE, set};
+
+pub const FIELD_TAG: u16 = 0x03;
+
+// Appends `extra` to the frame and fixes up its length prefix
+fn with_extra(frame: &[u8], extra: &[u8]) -> Bytes {
+    let mut raw = frame.to_vec();
+    raw.extend_from_slice(extra);
+    let len = raw.len() as u32;
+    raw[0..4].copy_from_slice(&len.to_be_bytes());
+    Bytes::from(raw)
+}
+
+#[test]
+fn test_strict_mode_rejects_suspicious_frames() {
+    let frame = set().to_bytes().unwrap();
+    let strict = RapidTlvParseOptions::strict().with_known_fields(&[FIELD_KEY, FIELD_VALUE]);
+    assert!(RapidTlvMessage::parse_with_options(frame.clone(), &strict).is_ok());
+
+    let trailing = with_extra(&frame, &[0, 0, 0]);
+    let err = RapidTlvMessage::parse_with_options(trailing, &strict).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(frame.len()));
+
+    let duplicate = with_extra(&frame, &[FIELD_KEY as u8, 0, 0, 0, 1, b'x']);
+    let err = RapidTlvMessage::parse_with_options(duplicate, &strict).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_KEY));
+    assert_eq!(err.offset(), Some(frame.len()));
+
+    let unknown = with_extra(&frame, &[FIELD_TAG as u8, 0, 0, 0, 0]);
+    let err = RapidTlvMessage::parse_with_options(unknown.clone(), &strict).unwrap_err();
+    assert_eq!(err.field_type(), Some(FIELD_TAG));
+
+    // Repeatable field types may occur more than once
+    let repeated = with_extra(&unknown, &[FIELD_TAG as u8, 0, 0, 0, 0]);
+    let strict = strict
+        .with_known_fields(&[FIELD_KEY, FIELD_VALUE, FIELD_TAG])
+        .with_repeated_fields(&[FIELD_TAG]);
+    let (msg, report) = RapidTlvMessage::parse_with_options(repeated, &strict).unwrap();
+    assert_eq!(msg.get_fields(&FIELD_TAG).count(), 2);
+    assert!(report.is_clean());
+}
+
+#[test]
+fn test_lenient_mode_reports_what_it_tolerated() {
+    let frame = set().to_bytes().unwrap();
+    #[rustfmt::skip]
+    let raw = with_extra(&frame, &[
+        FIELD_KEY as u8, 0, 0, 0, 1, b'x', // duplicate
+        FIELD_TAG as u8, 0, 0, 0, 0,       // unknown
+        FIELD_KEY as u8, 0, 0, 0, 0,       // duplicate again
+        0xAA, 0xBB,                        // trailing bytes
+    ]);
+
+    let lenient = RapidTlvParseOptions::lenient().with_known_fields(&[FIELD_KEY, FIELD_VALUE]);
+    let (msg, report) = RapidTlvMessage::parse_with_options(raw.clone(), &lenient).unwrap();
+    assert_eq!(report.trailing_bytes, 2);
+    assert_eq!(report.duplicate_fields, vec![FIELD_KEY]);
+    assert_eq!(report.unknown_fields, vec![FIELD_TAG]);
+    assert!(!report.is_clean());
+
+    // Nothing is dropped, and the last occurrence wins as in `parse`
+    assert_eq!(msg.get_field(&FIELD_KEY).unwrap().value(), b"");
+    assert_eq!(
+        msg.to_bytes().unwrap(),
+        RapidTlvMessage::parse(raw).unwrap().to_bytes().unwrap()
+    );
+}
+
+#[test]
+fn test_strict_mode_edge_cases() {
+    let frame = set().to_bytes().unwrap();
+
+    // Without a list of known fields, any field type is accepted
+    let strict = RapidTlvParseOptions::strict();
+    let tagged = with_extra(&frame, &[FIELD_TAG as u8, 0, 0, 0, 0]);
+    assert!(RapidTlvMessage::parse_with_options(tagged, &strict).is_ok());
+
+    // Every length of trailing garbage too short for a field header
+    let lenient = RapidTlvParseOptions::lenient();
+    for len in 1..=4 {
+        let trailing = with_extra(&frame, &vec![0; len]);
+        assert!(RapidTlvMessage::parse_with_options(trailing.clone(), &strict).is_err());
+        let (_, report) = RapidTlvMessage::parse_with_options(trailing, &lenient).unwrap();
+        assert_eq!(report.trailing_bytes, len);
+    }
+
+    // Field types above 255 are known or unknown like any other
+    let wide_type = 0x0102;
+    let config = RapidTlvWireConfig::legacy()
+        .with_format(RapidTlvWireFormat::new().with_field_type(RapidTlvFieldTypeWidth::U16));
+    let frame = set()
+        .with_field(wide_type, Bytes::from_static(b"w"))
+        .to_bytes_with(&config)
+        .unwrap();
+    let narrow = RapidTlvParseOptions::strict().with_known_fields(&[FIELD_KEY, FIELD_VALUE]);
+    let wide =
+        RapidTlvParseOptions::strict().with_known_fields(&[FIELD_KEY, FIELD_VALUE, wide_type]);
+    let err =
+        RapidTlvMessage::parse_with(frame.clone(), &config.clone().with_parse_options(narrow))
+            .unwrap_err();
+    assert_eq!(err.field_type(), Some(wide_type));
+    assert!(RapidTlvMessage::parse_with(frame, &config.with_parse_options(wide)).is_ok());
+}
+
+#[test]
+fn test_strict_transports() {
+    let frame = set().to_bytes().unwrap();
+    let mut stream = frame.to_vec();
+    stream.extend_from_slice(&with_extra(&frame, &[0]));
+
+    let config = RapidTlvWireConfig::legacy().with_parse_options(RapidTlvParseOptions::strict());
+    let mut decoder = RapidTlvFrameDecoder::new().with_config(config);
+    decoder.extend(&stream);
+    assert!(decoder.decode().unwrap().is_some());
+    let err = decoder.decode().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+
+    let versioned = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_checksum(true)
+        .with_parse_options(RapidTlvParseOptions::strict());
+    let frame = set()
+        .with_repeated_field(FIELD_VALUE, Bytes::from_static(b"again"))
+        .to_bytes_with(&versioned)
+        .unwrap();
+    let err = RapidTlvMessage::parse_with(frame, &versioned).unwrap_err();
+    assert_eq!(err.field_type(), Some(FIELD_VALUE));
+}
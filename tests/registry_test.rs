@@ -0,0 +1,181 @@
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    RapidTlvErrorCode, RapidTlvEventSchema, RapidTlvMessage, RapidTlvSchema,
+    RapidTlvSchemaRegistry, RapidTlvValidationError, RapidTlvValueType,
+};
+
+use common::{EVT_SET, FIELD_KEY, FIELD_VALUE};
+
+pub const EVT_BATCH: u8 = 0x20;
+
+pub const FIELD_TTL: u16 = 0x03;
+pub const FIELD_PERSIST: u16 = 0x04;
+pub const FIELD_GROUP: u16 = 0x05;
+pub const FIELD_RECORD: u16 = 0x09;
+
+const SCHEMA: &str = "
+field key = 0x01: string;
+field value = 0x02: bytes;
+field ttl = 0x03: u32;
+field persist = 0x04: bool;
+field group = 0x05: string;
+field record = 0x09: set;
+
+event set = 0x10 {
+    required key;
+    required value;
+    optional ttl;
+    repeated group;
+}
+
+event batch = 0x20 { repeated record; }
+";
+
+fn registry() -> RapidTlvSchemaRegistry {
+    RapidTlvSchemaRegistry::new()
+        .with_schema(&SCHEMA.parse().unwrap())
+        .unwrap()
+}
+
+fn set() -> RapidTlvMessage {
+    common::set()
+        .with_field(FIELD_TTL, Bytes::from_static(&[0, 0, 0, 60]))
+        .with_repeated_field(FIELD_GROUP, Bytes::from_static(b"a"))
+        .with_repeated_field(FIELD_GROUP, Bytes::from_static(b"b"))
+}
+
+#[test]
+fn test_validate_valid_messages() {
+    let registry = registry();
+    assert_eq!(registry.validate(&set()), Ok(()));
+
+    let batch = RapidTlvMessage::new(EVT_BATCH)
+        .with_message_field(FIELD_RECORD, set())
+        .unwrap();
+    assert_eq!(registry.validate(&batch), Ok(()));
+
+    // Nested messages are checked within the message's own limits
+    let mut shallow = batch.clone();
+    shallow.set_max_nesting_depth(0);
+    assert!(matches!(
+        registry.validate(&shallow).unwrap_err()[..],
+        [RapidTlvValidationError::InvalidValue {
+            field_type: FIELD_RECORD,
+            ..
+        }]
+    ));
+
+    // Unlisted fields pass unless the registry is strict
+    let extra = set().with_field(FIELD_PERSIST, Bytes::from_static(&[1]));
+    assert_eq!(registry.validate(&extra), Ok(()));
+    assert_eq!(
+        registry.with_strict(true).validate(&extra),
+        Err(vec![RapidTlvValidationError::UnknownField(FIELD_PERSIST)])
+    );
+}
+
+#[test]
+fn test_validate_reports_all_errors() {
+    let registry = registry();
+
+    let errors = registry.validate(&RapidTlvMessage::new(0x42)).unwrap_err();
+    assert_eq!(errors, vec![RapidTlvValidationError::UnknownEvent(0x42)]);
+    assert_eq!(errors[0].code(), RapidTlvErrorCode::InvalidEventType);
+
+    let mut msg = set()
+        .with_field(FIELD_TTL, Bytes::from_static(&[0, 60]))
+        .with_repeated_field(FIELD_KEY, Bytes::from_static(&[0xFF]));
+    msg.remove_field(FIELD_VALUE);
+
+    let errors = registry.validate(&msg).unwrap_err();
+    assert_eq!(errors.len(), 4, "{errors:?}");
+    assert!(errors.contains(&RapidTlvValidationError::MissingField(FIELD_VALUE)));
+    assert!(errors.contains(&RapidTlvValidationError::DuplicateField(FIELD_KEY)));
+    assert!(
+        errors
+            .iter()
+            .filter(|e| matches!(e, RapidTlvValidationError::InvalidValue { .. }))
+            .map(|e| e.field_type())
+            .eq([Some(FIELD_KEY), Some(FIELD_TTL)])
+    );
+
+    // Nested messages are validated against their event
+    let mut inner = set();
+    inner.remove_field(FIELD_KEY);
+    let batch = RapidTlvMessage::new(EVT_BATCH)
+        .with_message_field(FIELD_RECORD, inner)
+        .unwrap();
+    let errors = registry.validate(&batch).unwrap_err();
+    assert_eq!(errors[0].field_type(), Some(FIELD_RECORD));
+}
+
+#[test]
+fn test_validate_value_widths() {
+    let types = [
+        (RapidTlvValueType::U8, 1),
+        (RapidTlvValueType::U16, 2),
+        (RapidTlvValueType::U32, 4),
+        (RapidTlvValueType::U64, 8),
+        (RapidTlvValueType::I64, 8),
+        (RapidTlvValueType::F64, 8),
+        (RapidTlvValueType::Bool, 1),
+    ];
+    for (value_type, width) in types {
+        let schema = RapidTlvSchema::new()
+            .with_field("value", FIELD_VALUE, value_type.clone())
+            .with_event(RapidTlvEventSchema::new("set", EVT_SET).optional("value"));
+        let registry = RapidTlvSchemaRegistry::new().with_schema(&schema).unwrap();
+        let with_len =
+            |len| RapidTlvMessage::new(EVT_SET).with_field(FIELD_VALUE, Bytes::from(vec![1; len]));
+
+        assert_eq!(registry.validate(&with_len(width)), Ok(()), "{value_type}");
+        for len in [0, width - 1, width + 1] {
+            assert!(registry.validate(&with_len(len)).is_err(), "{value_type}");
+        }
+    }
+
+    // Booleans are 0 or 1, messages must parse
+    let schema = RapidTlvSchema::new()
+        .with_field("flag", FIELD_VALUE, RapidTlvValueType::Bool)
+        .with_field("inner", FIELD_TTL, RapidTlvValueType::Message)
+        .with_event(
+            RapidTlvEventSchema::new("set", EVT_SET)
+                .optional("flag")
+                .optional("inner"),
+        );
+    let registry = RapidTlvSchemaRegistry::new().with_schema(&schema).unwrap();
+    let msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_VALUE, Bytes::from_static(&[2]))
+        .with_field(FIELD_TTL, Bytes::from_static(&[0, 0, 0, 9, EVT_SET]));
+    let errors = registry.validate(&msg).unwrap_err();
+    assert_eq!(errors.len(), 2, "{errors:?}");
+}
+
+#[test]
+fn test_registry_from_code_and_files() {
+    // Built in code, sharing the `key` field with the parsed schema
+    let get = RapidTlvSchema::new()
+        .with_field("key", FIELD_KEY, RapidTlvValueType::String)
+        .with_event(RapidTlvEventSchema::new("get", 0x11).required("key"));
+
+    let mut registry = registry();
+    registry.register(&get).unwrap();
+    let msg = RapidTlvMessage::new(0x11).with_field(FIELD_KEY, Bytes::from_static(b"k"));
+    assert_eq!(registry.validate(&msg), Ok(()));
+
+    // Conflicting numbers are rejected
+    let clash = RapidTlvSchema::new().with_field("other", FIELD_KEY, RapidTlvValueType::U8);
+    assert!(registry.register(&clash).is_err());
+
+    let path = std::env::temp_dir().join(format!("rapid_tlv_registry_{}.tlv", std::process::id()));
+    std::fs::write(&path, SCHEMA).unwrap();
+    let mut loaded = RapidTlvSchemaRegistry::new();
+    loaded.load(&path).unwrap();
+    std::fs::remove_file(&path).unwrap();
+    assert_eq!(loaded.validate(&set()), Ok(()));
+
+    let err = loaded.load(&path).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ReadFailed);
+}
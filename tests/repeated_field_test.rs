@@ -0,0 +1,90 @@
+use bytes::Bytes;
+use rapid_tlv::RapidTlvMessage;
+
+pub const EVT_MGET: u8 = 0x13;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_GROUP: u16 = 0x05;
+
+fn values(msg: &RapidTlvMessage, field_type: u16) -> Vec<&[u8]> {
+    msg.get_fields(&field_type).map(|f| f.value()).collect()
+}
+
+#[test]
+fn test_repeated_field_roundtrip() {
+    // Several keys in one MGET survive encoding and parsing in order
+    let mut msg = RapidTlvMessage::new(EVT_MGET)
+        .with_repeated_field(FIELD_KEY, Bytes::from_static(b"a"))
+        .with_repeated_field(FIELD_KEY, Bytes::from_static(b"b"));
+    msg.add_repeated_field(FIELD_KEY, Bytes::from_static(b"c"));
+    msg.add_field(FIELD_GROUP, Bytes::from_static(b"g"));
+
+    assert_eq!(values(&msg, FIELD_KEY), vec![b"a", b"b", b"c"]);
+
+    // get_field returns the last occurrence
+    assert_eq!(msg.get_field(&FIELD_KEY).unwrap().value(), b"c");
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(values(&parsed, FIELD_KEY), vec![b"a", b"b", b"c"]);
+    assert_eq!(values(&parsed, FIELD_GROUP), vec![b"g"]);
+}
+
+#[test]
+fn test_parse_keeps_duplicates() {
+    let raw = Bytes::from_static(&[
+        0x00,
+        0x00,
+        0x00,
+        0x13,     // Message length = 19 bytes
+        EVT_MGET, // Event type
+        FIELD_KEY as u8,
+        0x00,
+        0x00,
+        0x00,
+        0x02,
+        b'k',
+        b'1', // First key
+        FIELD_KEY as u8,
+        0x00,
+        0x00,
+        0x00,
+        0x02,
+        b'k',
+        b'2', // Second key
+    ]);
+
+    let parsed = RapidTlvMessage::parse(raw).unwrap();
+    assert_eq!(values(&parsed, FIELD_KEY), vec![b"k1", b"k2"]);
+    assert_eq!(parsed.get_field(&FIELD_KEY).unwrap().value(), b"k2");
+}
+
+#[test]
+fn test_add_field_replaces_repeated_values() {
+    let mut msg = RapidTlvMessage::new(EVT_MGET)
+        .with_repeated_field(FIELD_KEY, Bytes::from_static(b"a"))
+        .with_repeated_field(FIELD_KEY, Bytes::from_static(b"b"));
+
+    msg.add_field(FIELD_KEY, Bytes::from_static(b"only"));
+    assert_eq!(values(&msg, FIELD_KEY), vec![b"only"]);
+
+    // remove_field drops every occurrence
+    msg.add_repeated_field(FIELD_KEY, Bytes::from_static(b"again"));
+    assert!(msg.remove_field(FIELD_KEY));
+    assert_eq!(msg.get_fields(&FIELD_KEY).count(), 0);
+}
+
+#[test]
+fn test_get_field_past_many_repeated_values() {
+    // Positions beyond what the lookup table holds, then a removal that shifts them
+    let mut msg = RapidTlvMessage::new(EVT_MGET);
+    for i in 0..300u32 {
+        msg.add_repeated_field(FIELD_KEY, Bytes::copy_from_slice(&i.to_be_bytes()));
+    }
+    msg.add_field(FIELD_GROUP, Bytes::from_static(b"g"));
+    assert_eq!(msg.get_u32(FIELD_KEY).unwrap(), Some(299));
+    assert_eq!(msg.get_field(&FIELD_GROUP).unwrap().value(), b"g");
+
+    assert!(msg.remove_field(FIELD_KEY));
+    assert_eq!(msg.get_field(&FIELD_GROUP).unwrap().value(), b"g");
+    assert!(msg.get_field(&FIELD_KEY).is_none());
+}
@@ -0,0 +1,96 @@
+use rapid_tlv::{RapidTlvPresence, RapidTlvSchema, RapidTlvValueType};
+
+const SCHEMA: &str = "
+// Key-value store
+field key = 0x01: string;
+field ttl = 3: u32;
+field record = 0x09: set;
+
+event set = 0x10 {
+    required key;
+    optional ttl;
+}
+
+event batch = 0x20 { repeated record; }
+";
+
+#[test]
+fn test_schema_parse() {
+    let schema: RapidTlvSchema = SCHEMA.parse().unwrap();
+    assert_eq!(schema.fields().len(), 3);
+    assert_eq!(schema.events().len(), 2);
+
+    let ttl = schema.field("ttl").unwrap();
+    assert_eq!(ttl.field_type, 0x03);
+    assert_eq!(ttl.value_type, RapidTlvValueType::U32);
+    assert_eq!(
+        schema.field_by_type(0x09).unwrap().value_type,
+        RapidTlvValueType::Event("set".into())
+    );
+
+    let set = schema.event_by_type(0x10).unwrap();
+    assert_eq!(set.name, "set");
+    assert_eq!(set.fields[0].name, "key");
+    assert_eq!(set.fields[0].presence, RapidTlvPresence::Required);
+    assert_eq!(set.fields[1].presence, RapidTlvPresence::Optional);
+    assert_eq!(
+        schema.event("batch").unwrap().fields[0].presence,
+        RapidTlvPresence::Repeated
+    );
+}
+
+#[test]
+fn test_schema_syntax_errors() {
+    let err =
+        RapidTlvSchema::parse("field key = 0x01: string;\nfield ttl = 0x10000: u32;").unwrap_err();
+    assert!(err.message().contains("line 2"), "{err}");
+
+    // Field types are two bytes wide, event types one
+    let schema = RapidTlvSchema::parse("field ttl = 0x100: u32;").unwrap();
+    assert_eq!(schema.field("ttl").unwrap().field_type, 0x100);
+    let err = RapidTlvSchema::parse("event set = 0x100 {}").unwrap_err();
+    assert!(err.message().contains("8 bits"), "{err}");
+
+    let err = RapidTlvSchema::parse("event set = 0x10 {\n  always key;\n}").unwrap_err();
+    assert!(err.message().contains("line 2"), "{err}");
+
+    let err = RapidTlvSchema::parse("field key = 0x01: string").unwrap_err();
+    assert!(err.message().contains("end of file"), "{err}");
+}
+
+#[test]
+fn test_schema_conflicts() {
+    for source in [
+        // Duplicate field number
+        "field key = 1: string; field other = 1: u8;",
+        // Unknown field and unknown type
+        "event set = 0x10 { required key; }",
+        "field key = 1: sett;",
+        // Field listed twice, reserved error event
+        "field key = 1: string; event set = 0x10 { required key; optional key; }",
+        "event error = 0xFF { }",
+        // Events that require themselves, directly or through another event
+        "field child = 1: node; event node = 0x10 { required child; }",
+        "field a = 1: ping; field b = 2: pong; \
+         event ping = 0x10 { required b; } event pong = 0x11 { required a; }",
+    ] {
+        assert!(RapidTlvSchema::parse(source).is_err(), "{source}");
+    }
+}
+
+#[test]
+fn test_schema_recursive_events() {
+    let schema = RapidTlvSchema::parse(
+        "field next = 1: node; field children = 2: node;
+         event node = 0x10 { optional next; repeated children; }",
+    )
+    .unwrap();
+    assert_eq!(
+        schema.field("next").unwrap().value_type,
+        RapidTlvValueType::Event("node".into())
+    );
+
+    let err = RapidTlvSchema::parse("field child = 1: node; event node = 0x10 { required child; }")
+        .unwrap_err();
+    assert!(err.message().contains("`node` requires itself"), "{err}");
+}
@@ -0,0 +1,143 @@
+#![cfg(feature = "serde")]
+
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvMessage, from_message, to_message};
+use serde::{Deserialize, Serialize};
+
+pub const EVT_SET: u8 = 0x10;
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Owner {
+    name: String,
+    id: u32,
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Set {
+    key: String,
+    #[serde(with = "serde_bytes")]
+    value: Vec<u8>,
+    ttl: Option<u32>,
+    persist: bool,
+    groups: Vec<String>,
+    owner: Owner,
+}
+
+fn sample() -> Set {
+    Set {
+        key: "test_key".into(),
+        value: b"test_value".to_vec(),
+        ttl: Some(60),
+        persist: true,
+        groups: vec!["a".into(), "b".into()],
+        owner: Owner {
+            name: "alice".into(),
+            id: 7,
+        },
+    }
+}
+
+#[test]
+fn test_serde_roundtrip() {
+    let set = sample();
+    let mut msg = to_message(EVT_SET, &set).unwrap();
+
+    // Fields are numbered in declaration order
+    assert_eq!(msg.event_type, EVT_SET);
+    assert_eq!(msg.get_field(&1).unwrap().value(), b"test_key");
+    assert_eq!(msg.get_field(&2).unwrap().value(), b"test_value");
+    assert_eq!(msg.get_field(&3).unwrap().value(), &[0, 0, 0, 60]);
+    assert_eq!(msg.get_field(&4).unwrap().value(), &[1]);
+    assert_eq!(msg.get_fields(&5).count(), 2);
+    let owner = msg.get_message_field(6).unwrap().unwrap();
+    assert_eq!(owner.get_field(&1).unwrap().value(), b"alice");
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(from_message::<Set>(&parsed).unwrap(), set);
+
+    // None is omitted and reads back as None
+    let no_ttl = Set {
+        ttl: None,
+        groups: vec![],
+        ..sample()
+    };
+    let msg = to_message(EVT_SET, &no_ttl).unwrap();
+    assert!(msg.get_field(&3).is_none());
+    assert!(msg.get_field(&5).is_none());
+    assert_eq!(from_message::<Set>(&msg).unwrap(), no_ttl);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Renamed<'a> {
+    #[serde(rename = "16")]
+    key: &'a str,
+    #[serde(rename = "32", with = "serde_bytes")]
+    value: &'a [u8],
+}
+
+#[test]
+fn test_serde_numbered_fields_borrow() {
+    let msg = to_message(
+        EVT_SET,
+        &Renamed {
+            key: "k",
+            value: b"v",
+        },
+    )
+    .unwrap();
+    assert_eq!(msg.get_field(&16).unwrap().value(), b"k");
+    assert_eq!(msg.get_field(&32).unwrap().value(), b"v");
+
+    // Strings and byte slices point into the message
+    let renamed: Renamed = from_message(&msg).unwrap();
+    assert_eq!(
+        renamed.key.as_ptr(),
+        msg.get_field(&16).unwrap().value().as_ptr()
+    );
+    assert_eq!(renamed.value, b"v");
+}
+
+#[test]
+fn test_serde_errors() {
+    // Missing required field
+    let mut msg = to_message(EVT_SET, &sample()).unwrap();
+    msg.remove_field(1);
+    let err = from_message::<Set>(&msg).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(1));
+
+    // Value with the wrong width
+    let mut msg = to_message(EVT_SET, &sample()).unwrap();
+    msg.add_field(3, Bytes::from_static(&[0, 60]));
+    let err = from_message::<Set>(&msg).unwrap_err();
+    assert_eq!(err.field_type(), Some(3));
+
+    // Nested structs count towards the message's nesting limit
+    let mut msg = to_message(EVT_SET, &sample()).unwrap();
+    msg.set_max_nesting_depth(0);
+    let err = from_message::<Set>(&msg).unwrap_err();
+    assert_eq!(err.field_type(), Some(6));
+
+    // Only structs map onto a message
+    let err = to_message(EVT_SET, &42u32).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+}
+
+#[derive(Debug, PartialEq, Serialize, Deserialize)]
+struct Clash {
+    a: u32,
+    #[serde(rename = "1")]
+    b: u32,
+}
+
+#[test]
+fn test_serde_rejects_shared_field_types() {
+    // `a` is numbered 1 by position, `b` by name
+    let err = to_message(EVT_SET, &Clash { a: 7, b: 8 }).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(1));
+
+    let msg = RapidTlvMessage::new(EVT_SET).with_field(1, Bytes::from_static(&[0, 0, 0, 7]));
+    let err = from_message::<Clash>(&msg).unwrap_err();
+    assert_eq!(err.field_type(), Some(1));
+}
@@ -0,0 +1,74 @@
+use bytes::Bytes;
+use rapid_tlv::{RapidTlvErrorCode, RapidTlvField, RapidTlvMessage};
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_TTL: u16 = 0x03;
+pub const FIELD_PERSIST: u16 = 0x04;
+pub const FIELD_TIMESTAMP: u16 = 0x06;
+pub const FIELD_VERSION: u16 = 0x07;
+pub const FIELD_ID: u16 = 0x08;
+
+#[test]
+fn test_typed_message_roundtrip() {
+    let mut msg = RapidTlvMessage::new(EVT_SET);
+    msg.set_str(FIELD_KEY, "test_key");
+    msg.set_u32(FIELD_TTL, 60);
+    msg.set_bool(FIELD_PERSIST, true);
+    msg.set_i64(FIELD_TIMESTAMP, -1_700_000_000);
+    msg.set_f64(FIELD_VERSION, 1.5);
+    msg.set_u64(FIELD_ID, u64::MAX - 1);
+
+    // Values use the protocol's big-endian convention
+    assert_eq!(msg.get_field(&FIELD_TTL).unwrap().value(), &[0, 0, 0, 60]);
+
+    let parsed = RapidTlvMessage::parse(Bytes::copy_from_slice(msg.encode().unwrap())).unwrap();
+    assert_eq!(parsed.get_str(FIELD_KEY).unwrap(), Some("test_key"));
+    assert_eq!(parsed.get_u32(FIELD_TTL).unwrap(), Some(60));
+    assert_eq!(parsed.get_bool(FIELD_PERSIST).unwrap(), Some(true));
+    assert_eq!(
+        parsed.get_i64(FIELD_TIMESTAMP).unwrap(),
+        Some(-1_700_000_000)
+    );
+    assert_eq!(parsed.get_f64(FIELD_VERSION).unwrap(), Some(1.5));
+    assert_eq!(parsed.get_u64(FIELD_ID).unwrap(), Some(u64::MAX - 1));
+
+    // Absent fields are not an error
+    assert_eq!(parsed.get_u32(0x42).unwrap(), None);
+}
+
+#[test]
+fn test_typed_field_accessors() {
+    let mut field = RapidTlvField::new(FIELD_TTL, Bytes::from_static(&[0, 0, 1, 44]));
+    assert_eq!(field.get_u32().unwrap(), 300);
+
+    field.set_u16(7);
+    assert_eq!(field.value(), &[0, 7]);
+    assert_eq!(field.get_u16().unwrap(), 7);
+
+    field.set_str("hallo");
+    assert_eq!(field.get_str().unwrap(), "hallo");
+}
+
+#[test]
+fn test_typed_accessor_errors() {
+    let msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_TTL, Bytes::from_static(&[0, 60]))
+        .with_field(FIELD_PERSIST, Bytes::from_static(&[2]))
+        .with_field(FIELD_KEY, Bytes::from_static(&[0xFF, 0xFE]));
+
+    // Wrong width
+    let err = msg.get_u32(FIELD_TTL).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_TTL));
+
+    // Booleans must be 0 or 1
+    let err = msg.get_bool(FIELD_PERSIST).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+
+    // Invalid UTF-8
+    let err = msg.get_str(FIELD_KEY).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.field_type(), Some(FIELD_KEY));
+}
@@ -0,0 +1,113 @@
+use bytes::Bytes;
+use rapid_tlv::{
+    RapidTlvErrorCode, RapidTlvField, RapidTlvFrameDecoder, RapidTlvMessage, RapidTlvReader,
+    RapidTlvWireConfig, RapidTlvWireFormat, RapidTlvWriter,
+};
+
+pub const EVT_SET: u8 = 0x10;
+
+pub const FIELD_KEY: u16 = 0x01;
+pub const FIELD_VALUE: u16 = 0x02;
+
+fn compact() -> RapidTlvWireConfig {
+    RapidTlvWireConfig::legacy().with_format(RapidTlvWireFormat::compact())
+}
+
+#[test]
+fn test_compact_encoding() {
+    let msg = RapidTlvMessage::new(EVT_SET)
+        .with_field(FIELD_KEY, Bytes::from_static(b"ab"))
+        .with_field(FIELD_VALUE, Bytes::from(vec![7u8; 200]));
+    let frame = msg.to_bytes_with(&compact()).unwrap();
+
+    // Prefix excludes itself: 1 event type + 4 for the key + 203 for the value
+    assert_eq!(&frame[0..2], &[0xD0, 0x01]);
+    assert_eq!(frame[2], EVT_SET);
+    assert_eq!(&frame[3..7], &[FIELD_KEY as u8, 0x02, b'a', b'b']);
+    // 200 = 0b1_1001000 needs two varint bytes
+    assert_eq!(&frame[7..10], &[FIELD_VALUE as u8, 0xC8, 0x01]);
+    assert_eq!(frame.len(), 210);
+    assert!(frame.len() < msg.to_bytes().unwrap().len());
+
+    let parsed = RapidTlvMessage::parse_with(frame, &compact()).unwrap();
+    assert_eq!(
+        parsed.get_field(&FIELD_VALUE).unwrap().value(),
+        &[7u8; 200][..]
+    );
+
+    // Field::encode shares the header logic
+    let field = RapidTlvField::new(FIELD_KEY, Bytes::from_static(b"ab"));
+    assert_eq!(
+        field
+            .encode_with(&RapidTlvWireFormat::compact())
+            .unwrap()
+            .as_ref(),
+        &[FIELD_KEY as u8, 0x02, b'a', b'b']
+    );
+    assert_eq!(
+        field.encode().unwrap(),
+        field.encode_with(&RapidTlvWireFormat::default()).unwrap()
+    );
+}
+
+#[test]
+fn test_inclusive_varint_prefix() {
+    let config = RapidTlvWireConfig::legacy()
+        .with_format(RapidTlvWireFormat::compact().with_length_inclusive(true));
+
+    // Around 127 bytes the prefix itself decides whether it needs a second byte
+    for value_len in 118..=126 {
+        let msg = RapidTlvMessage::new(EVT_SET)
+            .with_field(FIELD_VALUE, Bytes::from(vec![1u8; value_len]));
+        let frame = msg.to_bytes_with(&config).unwrap();
+        let parsed = RapidTlvMessage::parse_with(frame.clone(), &config).unwrap();
+        assert_eq!(
+            parsed.get_field(&FIELD_VALUE).unwrap().value().len(),
+            value_len
+        );
+
+        let mut stream = RapidTlvReader::new(frame.as_ref()).with_config(config.clone());
+        assert!(stream.read_message().unwrap().is_some());
+    }
+}
+
+#[test]
+fn test_compact_transports_and_errors() {
+    let mut writer = RapidTlvWriter::new(Vec::new()).with_config(compact());
+    for i in 0..3u8 {
+        let msg = RapidTlvMessage::new(EVT_SET).with_field(FIELD_KEY, Bytes::from(vec![i; 150]));
+        writer.write_message(&msg).unwrap();
+    }
+    let stream = writer.into_inner().unwrap();
+
+    // Byte-by-byte delivery splits the varint prefixes
+    let mut decoder = RapidTlvFrameDecoder::new().with_config(compact());
+    let mut messages = Vec::new();
+    for byte in &stream {
+        messages.extend(decoder.feed(std::slice::from_ref(byte)).unwrap());
+    }
+    assert_eq!(messages.len(), 3);
+
+    let read: Vec<_> = RapidTlvReader::new(stream.as_slice())
+        .with_config(compact())
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(
+        read[2].get_field(&FIELD_KEY).unwrap().value(),
+        &[2u8; 150][..]
+    );
+
+    // A prefix that never terminates is rejected
+    let err = RapidTlvFrameDecoder::new()
+        .with_config(compact())
+        .feed(&[0xFF; 6])
+        .unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+
+    // Truncated stream inside a varint prefix
+    let err = RapidTlvReader::new(&[0x80][..])
+        .with_config(compact())
+        .read_message()
+        .unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::IncompleteMessage);
+}
@@ -0,0 +1,123 @@
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    RapidTlvEndianness, RapidTlvErrorCode, RapidTlvFieldTypeWidth, RapidTlvFrameDecoder,
+    RapidTlvLengthWidth, RapidTlvMessage, RapidTlvReader, RapidTlvWireConfig, RapidTlvWireFormat,
+};
+
+use common::{EVT_SET, FIELD_KEY, FIELD_VALUE, set};
+
+#[test]
+fn test_little_endian_short_lengths() {
+    let format = RapidTlvWireFormat::new()
+        .with_message_length(RapidTlvLengthWidth::U16)
+        .with_field_length(RapidTlvLengthWidth::U16)
+        .with_endianness(RapidTlvEndianness::Little);
+    let config = RapidTlvWireConfig::legacy().with_format(format);
+
+    let frame = set().to_bytes_with(&config).unwrap();
+    #[rustfmt::skip]
+    assert_eq!(
+        frame.as_ref(),
+        &[
+            0x11, 0x00,                    // message length 17, little-endian
+            EVT_SET,
+            FIELD_KEY as u8, 0x03, 0x00, b'k', b'e', b'y',
+            FIELD_VALUE as u8, 0x05, 0x00, b'v', b'a', b'l', b'u', b'e',
+        ]
+    );
+
+    let parsed = RapidTlvMessage::parse_with(frame, &config).unwrap();
+    assert_eq!(parsed.get_field(&FIELD_KEY).unwrap().value(), b"key");
+    assert_eq!(parsed.get_field(&FIELD_VALUE).unwrap().value(), b"value");
+
+    // The default format is the crate's own
+    assert_eq!(
+        set().to_bytes_with(&RapidTlvWireConfig::default()).unwrap(),
+        set().to_bytes().unwrap()
+    );
+}
+
+#[test]
+fn test_exclusive_length_and_wide_field_types() {
+    let format = RapidTlvWireFormat::new()
+        .with_length_inclusive(false)
+        .with_field_type(RapidTlvFieldTypeWidth::U16)
+        .with_field_length(RapidTlvLengthWidth::U8);
+    let config = RapidTlvWireConfig::versioned(1)
+        .unwrap()
+        .with_format(format);
+
+    let frame = set().with_correlation_id(9).to_bytes_with(&config).unwrap();
+    // The prefix excludes its own four bytes
+    assert_eq!(&frame[0..4], &(frame.len() as u32 - 4).to_be_bytes());
+    assert_eq!(&frame[15..18], &[0x00, FIELD_KEY as u8, 0x03]);
+
+    let mut stream = frame.to_vec();
+    stream.extend_from_slice(&frame);
+    let messages = RapidTlvFrameDecoder::new()
+        .with_config(config.clone())
+        .feed(&stream)
+        .unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[1].correlation_id(), Some(9));
+
+    let read: Vec<_> = RapidTlvReader::new(stream.as_slice())
+        .with_config(config)
+        .collect::<Result<_, _>>()
+        .unwrap();
+    assert_eq!(read[0].get_field(&FIELD_VALUE).unwrap().value(), b"value");
+}
+
+#[test]
+fn test_wire_format_limits() {
+    let narrow = RapidTlvWireConfig::legacy()
+        .with_format(RapidTlvWireFormat::new().with_field_length(RapidTlvLengthWidth::U8));
+    let big = RapidTlvMessage::new(EVT_SET).with_field(FIELD_VALUE, Bytes::from(vec![0; 256]));
+    let err = big.to_bytes_with(&narrow).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+    assert_eq!(err.field_type(), Some(FIELD_VALUE));
+
+    // The largest value that fits still roundtrips
+    let largest = RapidTlvMessage::new(EVT_SET).with_field(FIELD_VALUE, Bytes::from(vec![0; 255]));
+    let frame = largest.to_bytes_with(&narrow).unwrap();
+    assert_eq!(frame[5..7], [FIELD_VALUE as u8, 0xFF]);
+    assert_eq!(
+        RapidTlvMessage::parse_with(frame, &narrow).unwrap(),
+        largest
+    );
+
+    // The message length bounds the whole frame
+    let short = RapidTlvWireConfig::legacy()
+        .with_format(RapidTlvWireFormat::new().with_message_length(RapidTlvLengthWidth::U16));
+    let big = RapidTlvMessage::new(EVT_SET).with_field(FIELD_VALUE, Bytes::from(vec![0; 65535]));
+    let err = big.to_bytes_with(&short).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+
+    // The format itself is not negotiable
+    let err = short.negotiate(&narrow).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ConfigInvalid);
+}
+
+#[test]
+fn test_two_byte_field_types() {
+    // Field types above 255 only fit two-byte field headers
+    let wide_type = 0x0102;
+    let msg = RapidTlvMessage::new(EVT_SET).with_field(wide_type, Bytes::from_static(b"w"));
+    let err = msg.to_bytes().unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::ValueTooLarge);
+    assert_eq!(err.field_type(), Some(wide_type));
+    assert_eq!(
+        msg.encode_canonical().unwrap_err().code(),
+        RapidTlvErrorCode::ValueTooLarge
+    );
+
+    let wide = RapidTlvWireConfig::legacy()
+        .with_format(RapidTlvWireFormat::new().with_field_type(RapidTlvFieldTypeWidth::U16));
+    let frame = msg.to_bytes_with(&wide).unwrap();
+    assert_eq!(&frame[5..7], &[0x01, 0x02]);
+    let parsed = RapidTlvMessage::parse_with(frame, &wide).unwrap();
+    assert_eq!(parsed.get_field(&wide_type).unwrap().value(), b"w");
+    assert_eq!(parsed.digest(), msg.digest());
+}
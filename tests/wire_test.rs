@@ -0,0 +1,106 @@
+mod common;
+
+use bytes::Bytes;
+use rapid_tlv::{
+    FLAG_CORRELATION_ID, RapidTlvErrorCode, RapidTlvFrameDecoder, RapidTlvMessage, RapidTlvReader,
+    RapidTlvWireConfig, RapidTlvWriter,
+};
+
+use common::{EVT_SET, FIELD_KEY, FIELD_VALUE, set};
+
+#[test]
+fn test_versioned_header_roundtrip() {
+    let config = RapidTlvWireConfig::versioned(1).unwrap();
+    let msg = set().with_correlation_id(42);
+    let frame = msg.to_bytes_with(&config).unwrap();
+
+    // [length][version][flags][event type][correlation id][fields]
+    assert_eq!(&frame[0..4], &(frame.len() as u32).to_be_bytes());
+    assert_eq!(frame[4], 1);
+    assert_eq!(frame[5], FLAG_CORRELATION_ID);
+    assert_eq!(frame[6], EVT_SET);
+    assert_eq!(&frame[7..15], &42u64.to_be_bytes());
+
+    let parsed = RapidTlvMessage::parse_with(frame, &config).unwrap();
+    assert_eq!(parsed.event_type, EVT_SET);
+    assert_eq!(parsed.correlation_id(), Some(42));
+    assert_eq!(parsed.get_field(&FIELD_VALUE).unwrap().value(), b"value");
+
+    // The legacy header is unchanged by default
+    let legacy = set().to_bytes_with(&RapidTlvWireConfig::default()).unwrap();
+    assert_eq!(legacy, set().to_bytes().unwrap());
+}
+
+#[test]
+fn test_unsupported_versions_and_flags() {
+    let config = RapidTlvWireConfig::versioned(1).unwrap();
+    let frame = set().to_bytes_with(&config).unwrap();
+
+    for version in [0, 9] {
+        let err = RapidTlvWireConfig::versioned(version).unwrap_err();
+        assert_eq!(err.code(), RapidTlvErrorCode::UnsupportedVersion);
+    }
+
+    let mut newer = frame.to_vec();
+    newer[4] = 2;
+    let err = RapidTlvMessage::parse_with(Bytes::from(newer), &config).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::UnsupportedVersion);
+    assert_eq!(err.offset(), Some(4));
+
+    let mut flagged = frame.to_vec();
+    flagged[5] = 0x80;
+    let err = RapidTlvMessage::parse_with(Bytes::from(flagged), &config).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(5));
+
+    // Negotiation falls back to the lower version or the legacy header
+    assert_eq!(config.negotiate(&config).unwrap(), config);
+    assert_eq!(
+        config.negotiate(&RapidTlvWireConfig::legacy()).unwrap(),
+        RapidTlvWireConfig::legacy()
+    );
+}
+
+#[test]
+fn test_truncated_versioned_headers() {
+    let config = RapidTlvWireConfig::versioned(1).unwrap();
+
+    // A legacy frame is too short for version and flags
+    let legacy = RapidTlvMessage::new(EVT_SET).to_bytes().unwrap();
+    let err = RapidTlvMessage::parse_with(legacy, &config).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(0));
+
+    // The smallest versioned frame has no fields and no correlation id
+    let empty = RapidTlvMessage::new(EVT_SET)
+        .to_bytes_with(&config)
+        .unwrap();
+    assert_eq!(empty.as_ref(), &[0, 0, 0, 7, 1, 0, EVT_SET]);
+
+    // The flag promises a correlation id the frame does not hold
+    let frame = Bytes::from_static(&[0, 0, 0, 11, 1, FLAG_CORRELATION_ID, EVT_SET, 0, 0, 0, 1]);
+    let err = RapidTlvMessage::parse_with(frame, &config).unwrap_err();
+    assert_eq!(err.code(), RapidTlvErrorCode::Malformed);
+    assert_eq!(err.offset(), Some(7));
+}
+
+#[test]
+fn test_versioned_transports() {
+    let config = RapidTlvWireConfig::versioned(1).unwrap();
+
+    let mut writer = RapidTlvWriter::new(Vec::new()).with_config(config.clone());
+    writer.write_message(&set().with_correlation_id(7)).unwrap();
+    writer.write_message(&set()).unwrap();
+    let stream = writer.into_inner().unwrap();
+
+    let mut decoder = RapidTlvFrameDecoder::new().with_config(config.clone());
+    let messages = decoder.feed(&stream).unwrap();
+    assert_eq!(messages.len(), 2);
+    assert_eq!(messages[0].correlation_id(), Some(7));
+    assert_eq!(messages[1].correlation_id(), None);
+
+    let reader = RapidTlvReader::new(stream.as_slice()).with_config(config);
+    let messages: Vec<_> = reader.collect::<Result<_, _>>().unwrap();
+    assert_eq!(messages[0].correlation_id(), Some(7));
+    assert_eq!(messages[1].get_field(&FIELD_KEY).unwrap().value(), b"key");
+}